@@ -1,86 +1,817 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount, Mint, Transfer as TokenTransfer, transfer};
+use anchor_spl::token_interface::spl_token_2022::{
+    self, extension::transfer_fee::TransferFeeConfig,
+};
+use anchor_spl::token_interface::{
+    close_account, get_mint_extension_data, harvest_withheld_tokens_to_mint, transfer_checked,
+    CloseAccount, HarvestWithheldTokensToMint, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
 use anchor_spl::associated_token::AssociatedToken;
 
 declare_id!("BYH7nGfE4hekSgVevYbwGuiwymMCtRgX7ecWHshCmwmU");
 
+const BPS_DENOMINATOR: u16 = 10_000;
+const MAX_RECIPIENTS: usize = 10;
+
 
 #[program]
 pub mod simple_escrow {
+    use anchor_lang::system_program::{transfer, Transfer};
+
     use super::*;
 
+    /// Escrows `amount` tokens. With `exchange` set, the single recipient
+    /// settles through `exchange` by paying the requested tokens; without it
+    /// the escrow is one-way and claimable for free.
     pub fn initialize_escrow(
         ctx: Context<InitializeEscrow>, 
+        seed: u64,
         amount: u64, 
-        receiver: Pubkey
+        terms: EscrowTerms,
+        exchange: Option<ExchangeTerms>
     ) -> Result<()> {
+        if let Some(exchange) = &exchange {
+            require!(exchange.taker_amount > 0, EscrowError::NotAnExchange);
+        }
+        let exchange = exchange.unwrap_or_default();
+        validate_terms(&terms, exchange.taker_amount)?;
+
         // Save escrow info
         let escrow = &mut ctx.accounts.escrow;
         escrow.initializer = ctx.accounts.initializer.key();
-        escrow.receiver = receiver;
+        escrow.mode = EscrowMode::Token;
         escrow.mint = ctx.accounts.mint.key();
-        escrow.amount = amount;
-        escrow.bump = ctx.bumps.vault_authority;
+        escrow.seed = seed;
+        escrow.bump = ctx.bumps.escrow;
+        escrow.vault_authority_bump = ctx.bumps.vault_authority;
+        escrow.taker_mint = exchange.taker_mint;
+        escrow.taker_amount = exchange.taker_amount;
+        escrow.apply_terms(terms);
 
         // Transfer tokens from initializer -> vault
         let cpi_ctx = CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
-            TokenTransfer {
+            TransferChecked {
                 from: ctx.accounts.initializer_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
                 to: ctx.accounts.vault.to_account_info(),
                 authority: ctx.accounts.initializer.to_account_info(),
             }
         );
+        transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        // Transfer-fee mints withhold part of the transfer, so only credit
+        // what actually arrived in the (freshly created) vault
+        ctx.accounts.vault.reload()?;
+        ctx.accounts.escrow.amount = ctx.accounts.vault.amount;
+
+        Ok(())
+    }
+
+    /// Escrows native SOL: lamports are held directly by the escrow PDA, no vault needed
+    pub fn initialize_sol_escrow(
+        ctx: Context<InitializeSolEscrow>,
+        seed: u64,
+        amount: u64,
+        terms: EscrowTerms
+    ) -> Result<()> {
+        validate_terms(&terms, 0)?;
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.initializer = ctx.accounts.initializer.key();
+        escrow.mode = EscrowMode::Sol;
+        escrow.mint = Pubkey::default();
+        escrow.amount = amount;
+        escrow.seed = seed;
+        escrow.bump = ctx.bumps.escrow;
+        escrow.vault_authority_bump = 0;
+        escrow.taker_mint = Pubkey::default();
+        escrow.taker_amount = 0;
+        escrow.apply_terms(terms);
+
+        // Transfer lamports from initializer -> escrow PDA
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.initializer.to_account_info(),
+                to: ctx.accounts.escrow.to_account_info(),
+            },
+        );
         transfer(cpi_ctx, amount)?;
 
         Ok(())
     }
 
     pub fn claim_escrow(ctx: Context<ClaimEscrow>) -> Result<()> {
-        let escrow_key = ctx.accounts.escrow.key();
-        let bump = ctx.accounts.escrow.bump;
+        require!(!ctx.accounts.escrow.disputed, EscrowError::DisputeOpen);
+        require!(ctx.accounts.escrow.taker_amount == 0, EscrowError::ExchangeRequired);
+
+        let now = Clock::get()?.unix_timestamp;
+        let receiver_key = ctx.accounts.receiver.key();
+        let escrow = &mut ctx.accounts.escrow;
+        let vested = escrow.vested_amount(now)?;
+        let total = escrow.amount;
+
+        let recipient = escrow
+            .recipients
+            .iter_mut()
+            .find(|r| r.address == receiver_key)
+            .ok_or(EscrowError::Unauthorized)?;
+        let claimable = recipient
+            .share_of(vested)?
+            .checked_sub(recipient.claimed)
+            .ok_or(EscrowError::Overflow)?;
+        require!(claimable > 0, EscrowError::NothingToClaim);
+
+        recipient.claimed = recipient
+            .claimed
+            .checked_add(claimable)
+            .ok_or(EscrowError::Overflow)?;
+        escrow.amount_claimed = escrow
+            .amount_claimed
+            .checked_add(claimable)
+            .ok_or(EscrowError::Overflow)?;
+
+        // The escrow is done once every recipient has received their full share
+        let mut fully_claimed = true;
+        for r in escrow.recipients.iter() {
+            if r.claimed < r.share_of(total)? {
+                fully_claimed = false;
+                break;
+            }
+        }
+
+        if escrow.mode == EscrowMode::Sol {
+            // Final claim sweeps everything above rent; the rent itself goes
+            // back to the initializer when the escrow closes
+            let payout = if fully_claimed { sol_balance(&escrow.to_account_info())? } else { claimable };
+            transfer_lamports(
+                &ctx.accounts.escrow.to_account_info(),
+                &ctx.accounts.receiver.to_account_info(),
+                payout,
+            )?;
+            if fully_claimed {
+                ctx.accounts.escrow.close(ctx.accounts.initializer.to_account_info())?;
+            }
+            return Ok(());
+        }
+
+        let escrow_key = escrow.key();
+        let bump = escrow.vault_authority_bump;
         let seeds = &[b"vault", escrow_key.as_ref(), &[bump]];
         let signer = &[&seeds[..]];
 
+        let VaultAccounts { token_program, vault, mint, vault_authority } = vault_accounts(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault,
+            &ctx.accounts.mint,
+            &ctx.accounts.vault_authority,
+        )?;
+        let receiver_token_account = ctx
+            .accounts
+            .receiver_token_account
+            .as_ref()
+            .ok_or(EscrowError::MissingTokenAccounts)?;
+
         // Transfer tokens from vault -> receiver
-        let cpi_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            TokenTransfer {
-                from: ctx.accounts.vault.to_account_info(),
-                to: ctx.accounts.receiver_token_account.to_account_info(),
-                authority: ctx.accounts.vault_authority.to_account_info(),
-            },
-            signer
+        if !fully_claimed {
+            transfer_from_vault(
+                token_program,
+                vault,
+                mint,
+                receiver_token_account.to_account_info(),
+                vault_authority,
+                signer,
+                claimable,
+            )?;
+            return Ok(());
+        }
+
+        // Final claim: sweep the full vault balance, including rounding dust, so
+        // the vault can always be closed, even if someone sent extra tokens to it
+        transfer_from_vault(
+            token_program,
+            vault,
+            mint,
+            receiver_token_account.to_account_info(),
+            vault_authority,
+            signer,
+            vault.amount,
+        )?;
+
+        // Close the vault and return its rent to the initializer who paid it
+        close_vault(
+            token_program,
+            vault,
+            mint,
+            ctx.accounts.initializer.to_account_info(),
+            vault_authority,
+            signer,
+        )?;
+
+        ctx.accounts.escrow.close(ctx.accounts.initializer.to_account_info())?;
+
+        Ok(())
+    }
+
+    pub fn exchange(ctx: Context<Exchange>) -> Result<()> {
+        require!(!ctx.accounts.escrow.disputed, EscrowError::DisputeOpen);
+        let taker_amount = ctx.accounts.escrow.taker_amount;
+        require!(taker_amount > 0, EscrowError::NotAnExchange);
+
+        // Transfer requested tokens from receiver -> initializer
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.taker_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.receiver_taker_token_account.to_account_info(),
+                mint: ctx.accounts.taker_mint.to_account_info(),
+                to: ctx.accounts.initializer_taker_token_account.to_account_info(),
+                authority: ctx.accounts.receiver.to_account_info(),
+            }
+        );
+        transfer_checked(cpi_ctx, taker_amount, ctx.accounts.taker_mint.decimals)?;
+
+        let escrow_key = ctx.accounts.escrow.key();
+        let bump = ctx.accounts.escrow.vault_authority_bump;
+        let seeds = &[b"vault", escrow_key.as_ref(), &[bump]];
+        let signer = &[&seeds[..]];
+
+        // Transfer vaulted tokens from vault -> receiver
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault,
+            &ctx.accounts.mint,
+            ctx.accounts.receiver_token_account.to_account_info(),
+            &ctx.accounts.vault_authority,
+            signer,
+            ctx.accounts.vault.amount,
+        )?;
+
+        // Close the vault and return its rent to the initializer who paid it
+        close_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.vault,
+            &ctx.accounts.mint,
+            ctx.accounts.initializer.to_account_info(),
+            &ctx.accounts.vault_authority,
+            signer,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn cancel_escrow(ctx: Context<CancelEscrow>) -> Result<()> {
+        require!(!ctx.accounts.escrow.disputed, EscrowError::DisputeOpen);
+
+        // A deadline of 0 means the initializer can cancel at any time
+        let now = Clock::get()?.unix_timestamp;
+        let deadline = ctx.accounts.escrow.deadline;
+        if deadline != 0 {
+            require!(now >= deadline, EscrowError::DeadlineNotReached);
+        }
+
+        let escrow_key = ctx.accounts.escrow.key();
+        let bump = ctx.accounts.escrow.vault_authority_bump;
+        let seeds = &[b"vault", escrow_key.as_ref(), &[bump]];
+        let signer = &[&seeds[..]];
+        let is_sol = ctx.accounts.escrow.mode == EscrowMode::Sol;
+        let token_accounts = if is_sol {
+            None
+        } else {
+            Some(vault_accounts(
+                &ctx.accounts.token_program,
+                &ctx.accounts.vault,
+                &ctx.accounts.mint,
+                &ctx.accounts.vault_authority,
+            )?)
+        };
+
+        // Vested tokens already belong to the recipients: only the unvested
+        // rest is refunded, and the escrow stays open for the unclaimed part
+        let escrow = &mut ctx.accounts.escrow;
+        if escrow.release_end != 0 {
+            let vested = escrow.vested_amount(now)?;
+            if vested > escrow.amount_claimed {
+                let unvested = escrow
+                    .amount
+                    .checked_sub(vested)
+                    .ok_or(EscrowError::Overflow)?;
+                escrow.amount = vested;
+                // Ends the schedule now, so what vested stays fully claimable
+                escrow.release_end = escrow.release_end.min(now);
+
+                if unvested > 0 {
+                    if let Some(accounts) = &token_accounts {
+                        let initializer_token_account = ctx
+                            .accounts
+                            .initializer_token_account
+                            .as_ref()
+                            .ok_or(EscrowError::MissingTokenAccounts)?;
+                        transfer_from_vault(
+                            accounts.token_program,
+                            accounts.vault,
+                            accounts.mint,
+                            initializer_token_account.to_account_info(),
+                            accounts.vault_authority,
+                            signer,
+                            unvested,
+                        )?;
+                    } else {
+                        transfer_lamports(
+                            &ctx.accounts.escrow.to_account_info(),
+                            &ctx.accounts.initializer.to_account_info(),
+                            unvested,
+                        )?;
+                    }
+                }
+
+                msg!("Escrow cancelled: {} refunded, {} left for recipients", unvested, vested);
+                return Ok(());
+            }
+        }
+
+        // SOL mode: closing the escrow refunds every lamport it holds
+        let Some(VaultAccounts { token_program, vault, mint, vault_authority }) = token_accounts else {
+            return ctx.accounts.escrow.close(ctx.accounts.initializer.to_account_info());
+        };
+
+        let initializer_token_account = ctx
+            .accounts
+            .initializer_token_account
+            .as_ref()
+            .ok_or(EscrowError::MissingTokenAccounts)?;
+
+        // Refund whatever is left in the vault -> initializer
+        transfer_from_vault(
+            token_program,
+            vault,
+            mint,
+            initializer_token_account.to_account_info(),
+            vault_authority,
+            signer,
+            vault.amount,
+        )?;
+
+        // Close the now-empty vault and return its rent to the initializer
+        close_vault(
+            token_program,
+            vault,
+            mint,
+            ctx.accounts.initializer.to_account_info(),
+            vault_authority,
+            signer,
+        )?;
+
+        ctx.accounts.escrow.close(ctx.accounts.initializer.to_account_info())?;
+
+        Ok(())
+    }
+
+    pub fn raise_dispute(ctx: Context<RaiseDispute>) -> Result<()> {
+        let party = ctx.accounts.party.key();
+        let escrow = &mut ctx.accounts.escrow;
+        require!(
+            party == escrow.initializer || escrow.recipients.iter().any(|r| r.address == party),
+            EscrowError::Unauthorized
         );
-        transfer(cpi_ctx, ctx.accounts.escrow.amount)?;
+        require!(escrow.arbiter.is_some(), EscrowError::NoArbiter);
+        require!(!escrow.disputed, EscrowError::DisputeOpen);
 
+        escrow.disputed = true;
+
+        msg!("Dispute raised by {}", party);
+        Ok(())
+    }
+
+    /// Arbiter-only: splits the escrow between recipients (`split_bps`) and initializer (rest).
+    /// Recipient token accounts (SOL mode: recipient wallets) are passed as remaining
+    /// accounts, in escrow order.
+    pub fn resolve_dispute<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ResolveDispute<'info>>,
+        split_bps: u16
+    ) -> Result<()> {
+        require!(ctx.accounts.escrow.disputed, EscrowError::NoDispute);
+        require!(split_bps <= BPS_DENOMINATOR, EscrowError::InvalidSplit);
+
+        let recipients = ctx.accounts.escrow.recipients.clone();
+        require!(
+            ctx.remaining_accounts.len() == recipients.len(),
+            EscrowError::InvalidRecipients
+        );
+
+        let is_sol = ctx.accounts.escrow.mode == EscrowMode::Sol;
+        let token_accounts = if is_sol {
+            None
+        } else {
+            Some(vault_accounts(
+                &ctx.accounts.token_program,
+                &ctx.accounts.vault,
+                &ctx.accounts.mint,
+                &ctx.accounts.vault_authority,
+            )?)
+        };
+        let balance = match &token_accounts {
+            Some(accounts) => accounts.vault.amount,
+            None => sol_balance(&ctx.accounts.escrow.to_account_info())?,
+        };
+        let recipients_share = (balance as u128)
+            .checked_mul(split_bps as u128)
+            .ok_or(EscrowError::Overflow)?
+            / BPS_DENOMINATOR as u128;
+        let recipients_share = recipients_share as u64;
+        let initializer_share = balance
+            .checked_sub(recipients_share)
+            .ok_or(EscrowError::Overflow)?;
+
+        let escrow_key = ctx.accounts.escrow.key();
+        let bump = ctx.accounts.escrow.vault_authority_bump;
+        let seeds = &[b"vault", escrow_key.as_ref(), &[bump]];
+        let signer = &[&seeds[..]];
+
+        // Recipients' cut is split by what each is still owed, so earlier
+        // claims aren't paid out a second time
+        let total = ctx.accounts.escrow.amount;
+        let mut outstanding = Vec::with_capacity(recipients.len());
+        for recipient in recipients.iter() {
+            outstanding.push(recipient.share_of(total)?.saturating_sub(recipient.claimed) as u128);
+        }
+        let total_outstanding: u128 = outstanding.iter().sum();
+
+        // Transfer each recipient's cut out of the escrow; the last one also
+        // takes the rounding remainder
+        let mut remaining = recipients_share;
+        for (i, (recipient, destination)) in
+            recipients.iter().zip(ctx.remaining_accounts.iter()).enumerate()
+        {
+            let cut = if i + 1 == recipients.len() {
+                remaining
+            } else {
+                // Nothing outstanding at all: fall back to the plain shares
+                match (recipients_share as u128)
+                    .checked_mul(outstanding[i])
+                    .ok_or(EscrowError::Overflow)?
+                    .checked_div(total_outstanding)
+                {
+                    Some(cut) => cut as u64,
+                    None => recipient.share_of(recipients_share)?,
+                }
+            };
+            remaining = remaining.checked_sub(cut).ok_or(EscrowError::Overflow)?;
+
+            let Some(accounts) = &token_accounts else {
+                require_keys_eq!(destination.key(), recipient.address, EscrowError::InvalidRecipients);
+                if cut > 0 {
+                    transfer_lamports(&ctx.accounts.escrow.to_account_info(), destination, cut)?;
+                }
+                continue;
+            };
+
+            let token_account = InterfaceAccount::<TokenAccount>::try_from(destination)?;
+            require_keys_eq!(token_account.mint, ctx.accounts.escrow.mint, EscrowError::InvalidRecipients);
+            require_keys_eq!(token_account.owner, recipient.address, EscrowError::InvalidRecipients);
+            if cut == 0 {
+                continue;
+            }
+
+            transfer_from_vault(
+                accounts.token_program,
+                accounts.vault,
+                accounts.mint,
+                destination.clone(),
+                accounts.vault_authority,
+                signer,
+                cut,
+            )?;
+        }
+
+        // SOL mode: the initializer's share and the rent are refunded when the
+        // escrow closes
+        if let Some(VaultAccounts { token_program, vault, mint, vault_authority }) = token_accounts {
+
+            // Transfer the rest from vault -> initializer
+            if initializer_share > 0 {
+                let initializer_token_account = ctx
+                    .accounts
+                    .initializer_token_account
+                    .as_ref()
+                    .ok_or(EscrowError::MissingTokenAccounts)?;
+                transfer_from_vault(
+                    token_program,
+                    vault,
+                    mint,
+                    initializer_token_account.to_account_info(),
+                    vault_authority,
+                    signer,
+                    initializer_share,
+                )?;
+            }
+
+            // Close the now-empty vault and return its rent to the initializer
+            close_vault(
+                token_program,
+                vault,
+                mint,
+                ctx.accounts.initializer.to_account_info(),
+                vault_authority,
+                signer,
+            )?;
+        }
+
+        msg!(
+            "Dispute resolved: {} to recipients, {} to initializer",
+            recipients_share,
+            initializer_share
+        );
         Ok(())
     }
 }
 
+/// Checks the recipient split and release schedule shared by both escrow modes.
+/// `taker_amount` is 0 for one-way escrows.
+fn validate_terms(terms: &EscrowTerms, taker_amount: u64) -> Result<()> {
+    let (release_start, cliff, release_end) = (terms.release_start, terms.cliff, terms.release_end);
+    let recipients = &terms.recipients;
+
+    // A release_end of 0 means no schedule: everything is claimable at once
+    if release_end != 0 {
+        require!(
+            release_start < release_end && release_start <= cliff && cliff <= release_end,
+            EscrowError::InvalidSchedule
+        );
+        require!(taker_amount == 0, EscrowError::InvalidSchedule);
+    } else {
+        require!(release_start == 0 && cliff == 0, EscrowError::InvalidSchedule);
+    }
+
+    // Shares must cover exactly 100% across distinct recipients, and a swap
+    // has exactly one counterparty
+    require!(
+        !recipients.is_empty() && recipients.len() <= MAX_RECIPIENTS,
+        EscrowError::InvalidRecipients
+    );
+    let total_bps = recipients
+        .iter()
+        .try_fold(0u16, |acc, r| acc.checked_add(r.share_bps))
+        .ok_or(EscrowError::InvalidRecipients)?;
+    require!(total_bps == BPS_DENOMINATOR, EscrowError::InvalidRecipients);
+    for (i, r) in recipients.iter().enumerate() {
+        require!(
+            !recipients[..i].iter().any(|other| other.address == r.address),
+            EscrowError::InvalidRecipients
+        );
+    }
+    require!(
+        taker_amount == 0 || recipients.len() == 1,
+        EscrowError::InvalidRecipients
+    );
+
+    Ok(())
+}
+
+/// SOL-mode balance still held by the escrow, excluding its rent-exempt minimum
+fn sol_balance(escrow: &AccountInfo) -> Result<u64> {
+    let rent = Rent::get()?.minimum_balance(escrow.data_len());
+    escrow
+        .lamports()
+        .checked_sub(rent)
+        .ok_or(EscrowError::Overflow.into())
+}
+
+/// Token-mode accounts every vault transfer needs. They are optional in the
+/// instruction accounts only so SOL escrows can pass `null`.
+struct VaultAccounts<'a, 'info> {
+    token_program: &'a Interface<'info, TokenInterface>,
+    vault: &'a InterfaceAccount<'info, TokenAccount>,
+    mint: &'a InterfaceAccount<'info, Mint>,
+    vault_authority: &'a UncheckedAccount<'info>,
+}
+
+/// Unwraps the token-mode accounts together, failing if any is missing
+fn vault_accounts<'a, 'info>(
+    token_program: &'a Option<Interface<'info, TokenInterface>>,
+    vault: &'a Option<InterfaceAccount<'info, TokenAccount>>,
+    mint: &'a Option<InterfaceAccount<'info, Mint>>,
+    vault_authority: &'a Option<UncheckedAccount<'info>>,
+) -> Result<VaultAccounts<'a, 'info>> {
+    match (token_program, vault, mint, vault_authority) {
+        (Some(token_program), Some(vault), Some(mint), Some(vault_authority)) => Ok(VaultAccounts {
+            token_program,
+            vault,
+            mint,
+            vault_authority,
+        }),
+        _ => err!(EscrowError::MissingTokenAccounts),
+    }
+}
+
+/// Moves lamports out of a program-owned account (the SOL-mode escrow PDA)
+fn transfer_lamports<'info>(
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    **from.try_borrow_mut_lamports()? -= amount;
+    **to.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
+
+/// Moves `amount` out of the vault, signed by the vault authority PDA
+fn transfer_from_vault<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: AccountInfo<'info>,
+    vault_authority: &UncheckedAccount<'info>,
+    signer: &[&[&[u8]]],
+    amount: u64,
+) -> Result<()> {
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        TransferChecked {
+            from: vault.to_account_info(),
+            mint: mint.to_account_info(),
+            to,
+            authority: vault_authority.to_account_info(),
+        },
+        signer
+    );
+    transfer_checked(cpi_ctx, amount, mint.decimals)
+}
+
+/// Closes the emptied vault, first sweeping any Token-2022 transfer fees
+/// withheld in it to the mint, since those would otherwise block the close
+fn close_vault<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    destination: AccountInfo<'info>,
+    vault_authority: &UncheckedAccount<'info>,
+    signer: &[&[&[u8]]],
+) -> Result<()> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner == spl_token_2022::ID
+        && get_mint_extension_data::<TransferFeeConfig>(&mint_info).is_ok()
+    {
+        let cpi_ctx = CpiContext::new(
+            token_program.to_account_info(),
+            HarvestWithheldTokensToMint {
+                token_program_id: token_program.to_account_info(),
+                mint: mint_info,
+            },
+        );
+        harvest_withheld_tokens_to_mint(cpi_ctx, vec![vault.to_account_info()])?;
+    }
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: vault.to_account_info(),
+            destination,
+            authority: vault_authority.to_account_info(),
+        },
+        signer
+    );
+    close_account(cpi_ctx)
+}
+
 #[account]
 pub struct Escrow {
     pub initializer: Pubkey,
-    pub receiver: Pubkey,
     pub mint: Pubkey,
     pub amount: u64,
     pub bump: u8,
+    pub deadline: i64,
+    pub taker_mint: Pubkey,
+    pub taker_amount: u64,
+    pub seed: u64,
+    pub vault_authority_bump: u8,
+    pub release_start: i64,
+    pub cliff: i64,
+    pub release_end: i64,
+    pub amount_claimed: u64,
+    pub arbiter: Option<Pubkey>,
+    pub disputed: bool,
+    pub mode: EscrowMode,
+    // Kept last so the fixed fields above stay at stable offsets
+    pub recipients: Vec<Recipient>,
+}
+
+/// What the escrow holds: SPL tokens in a vault, or native SOL in the escrow PDA itself
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum EscrowMode {
+    Token,
+    Sol,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Recipient {
+    pub address: Pubkey,
+    pub share_bps: u16,
+    pub claimed: u64,
+}
+
+impl Recipient {
+    pub const SIZE: usize = 32 + 2 + 8;
+
+    /// This recipient's basis-point share of `total`
+    pub fn share_of(&self, total: u64) -> Result<u64> {
+        let share = (total as u128)
+            .checked_mul(self.share_bps as u128)
+            .ok_or(EscrowError::Overflow)?
+            / BPS_DENOMINATOR as u128;
+        Ok(share as u64)
+    }
+}
+
+/// Settlement terms shared by token and SOL escrows
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct EscrowTerms {
+    pub recipients: Vec<RecipientShare>,
+    pub deadline: i64, // 0: the initializer can cancel at any time
+    pub release_start: i64,
+    pub cliff: i64,
+    pub release_end: i64, // 0: no schedule, everything is claimable at once
+    pub arbiter: Option<Pubkey>,
+}
+
+/// What the recipient pays to settle a token escrow through `exchange`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ExchangeTerms {
+    pub taker_mint: Pubkey,
+    pub taker_amount: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RecipientShare {
+    pub address: Pubkey,
+    pub share_bps: u16,
+}
+
+impl From<&RecipientShare> for Recipient {
+    fn from(share: &RecipientShare) -> Self {
+        Recipient {
+            address: share.address,
+            share_bps: share.share_bps,
+            claimed: 0,
+        }
+    }
+}
+
+impl Escrow {
+    pub fn space(recipients: usize) -> usize {
+        8 + 32 + 32 + 8 + 1 + 8 + 32 + 8 + 8 + 1 + 8 + 8 + 8 + 8 + (1 + 32) + 1 + 1
+            + 4 + recipients * Recipient::SIZE
+    }
+
+    /// Copies the validated `terms` into a fresh escrow
+    fn apply_terms(&mut self, terms: EscrowTerms) {
+        self.recipients = terms.recipients.iter().map(Recipient::from).collect();
+        self.deadline = terms.deadline;
+        self.release_start = terms.release_start;
+        self.cliff = terms.cliff;
+        self.release_end = terms.release_end;
+        self.arbiter = terms.arbiter;
+        self.amount_claimed = 0;
+        self.disputed = false;
+    }
+
+    /// Amount released to all recipients so far under the linear schedule
+    pub fn vested_amount(&self, now: i64) -> Result<u64> {
+        if self.release_end == 0 || now >= self.release_end {
+            return Ok(self.amount);
+        }
+        if now < self.cliff || now <= self.release_start {
+            return Ok(0);
+        }
+
+        let elapsed = (now - self.release_start) as u128;
+        let duration = (self.release_end - self.release_start) as u128;
+        let vested = (self.amount as u128)
+            .checked_mul(elapsed)
+            .ok_or(EscrowError::Overflow)?
+            / duration;
+
+        Ok(vested as u64)
+    }
 }
 
 #[derive(Accounts)]
+#[instruction(seed: u64, amount: u64, terms: EscrowTerms)]
 pub struct InitializeEscrow<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+
     #[account(
         init,
         payer = initializer,
-        space = 8 + 32 + 32 + 32 + 8 + 1
+        space = Escrow::space(terms.recipients.len()),
+        seeds = [b"escrow", initializer.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump
     )]
     pub escrow: Account<'info, Escrow>,
 
-    #[account(mut)]
-    pub initializer: Signer<'info>,
-
-    #[account(mut)]
-    pub initializer_token_account: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub initializer_token_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         seeds = [b"vault", escrow.key().as_ref()],
@@ -93,39 +824,267 @@ pub struct InitializeEscrow<'info> {
         init,
         payer = initializer,
         associated_token::mint = mint,
-        associated_token::authority = vault_authority
+        associated_token::authority = vault_authority,
+        associated_token::token_program = token_program
     )]
-    pub vault: Account<'info, TokenAccount>,
+    pub vault: InterfaceAccount<'info, TokenAccount>,
 
-    pub mint: Account<'info, Mint>,
+    pub mint: InterfaceAccount<'info, Mint>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[derive(Accounts)]
+#[instruction(seed: u64, amount: u64, terms: EscrowTerms)]
+pub struct InitializeSolEscrow<'info> {
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = initializer,
+        space = Escrow::space(terms.recipients.len()),
+        seeds = [b"escrow", initializer.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct ClaimEscrow<'info> {
-    #[account(mut, has_one = receiver)]
+    #[account(
+        mut,
+        seeds = [b"escrow", initializer.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        has_one = initializer
+    )]
     pub escrow: Account<'info, Escrow>,
 
     #[account(
         seeds = [b"vault", escrow.key().as_ref()],
-        bump = escrow.bump
+        bump = escrow.vault_authority_bump
     )]
     /// CHECK: PDA authority
-    pub vault_authority: UncheckedAccount<'info>,
+    pub vault_authority: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = escrow.mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
 
     #[account(mut)]
-    pub vault: Account<'info, TokenAccount>,
+    pub receiver: Signer<'info>,
+
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub receiver_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Only receives the escrow and vault rent, checked by `has_one` on escrow
+    #[account(mut)]
+    pub initializer: UncheckedAccount<'info>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct Exchange<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", initializer.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        has_one = initializer,
+        constraint = escrow.recipients.len() == 1
+            && escrow.recipients[0].address == receiver.key() @ EscrowError::Unauthorized,
+        close = initializer
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        seeds = [b"vault", escrow.key().as_ref()],
+        bump = escrow.vault_authority_bump
+    )]
+    /// CHECK: PDA authority
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, address = escrow.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
 
     #[account(mut)]
     pub receiver: Signer<'info>,
 
+    /// Receives the vaulted tokens
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub receiver_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Pays the requested tokens
+    #[account(
+        mut,
+        token::mint = taker_mint,
+        token::authority = receiver,
+        token::token_program = taker_token_program
+    )]
+    pub receiver_taker_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Only receives the escrow and vault rent, checked by `has_one` on escrow
+    #[account(mut)]
+    pub initializer: UncheckedAccount<'info>,
+
+    /// Receives the requested tokens
+    #[account(
+        mut,
+        token::mint = taker_mint,
+        token::authority = initializer,
+        token::token_program = taker_token_program
+    )]
+    pub initializer_taker_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = escrow.taker_mint)]
+    pub taker_mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub taker_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CancelEscrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", initializer.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        has_one = initializer
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        seeds = [b"vault", escrow.key().as_ref()],
+        bump = escrow.vault_authority_bump
+    )]
+    /// CHECK: PDA authority
+    pub vault_authority: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = escrow.mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub initializer: Signer<'info>,
+
+    #[account(mut, token::mint = mint, token::token_program = token_program)]
+    pub initializer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+#[derive(Accounts)]
+pub struct RaiseDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", escrow.initializer.as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    pub party: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"escrow", initializer.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        has_one = initializer,
+        constraint = escrow.arbiter == Some(arbiter.key()) @ EscrowError::Unauthorized,
+        close = initializer
+    )]
+    pub escrow: Account<'info, Escrow>,
+
+    #[account(
+        seeds = [b"vault", escrow.key().as_ref()],
+        bump = escrow.vault_authority_bump
+    )]
+    /// CHECK: PDA authority
+    pub vault_authority: Option<UncheckedAccount<'info>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub vault: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = escrow.mint)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub arbiter: Signer<'info>,
+
+    /// CHECK: Only receives the escrow and vault rent, checked by `has_one` on escrow
     #[account(mut)]
-    pub receiver_token_account: Account<'info, TokenAccount>,
+    pub initializer: UncheckedAccount<'info>,
 
-    pub token_program: Program<'info, Token>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = initializer,
+        token::token_program = token_program
+    )]
+    pub initializer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
 }
 
+#[error_code]
+pub enum EscrowError {
+    #[msg("Escrow deadline has not been reached yet")]
+    DeadlineNotReached,
+    #[msg("This escrow must be settled through exchange")]
+    ExchangeRequired,
+    #[msg("This escrow does not request any tokens in exchange")]
+    NotAnExchange,
+    #[msg("Invalid release schedule")]
+    InvalidSchedule,
+    #[msg("Nothing has vested yet")]
+    NothingToClaim,
+    #[msg("Arithmetic overflow")]
+    Overflow,
+    #[msg("Unauthorized access")]
+    Unauthorized,
+    #[msg("This escrow has no arbiter")]
+    NoArbiter,
+    #[msg("A dispute is open on this escrow")]
+    DisputeOpen,
+    #[msg("No dispute is open on this escrow")]
+    NoDispute,
+    #[msg("Split must be at most 10000 basis points")]
+    InvalidSplit,
+    #[msg("Recipients must be distinct, at most 10, and their shares must sum to 10000 basis points")]
+    InvalidRecipients,
+    #[msg("Token escrows require the vault, mint and token accounts")]
+    MissingTokenAccounts,
+}
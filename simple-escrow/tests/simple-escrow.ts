@@ -1,8 +1,20 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { SimpleEscrow } from "../target/types/simple_escrow";
-import { PublicKey, SystemProgram, SYSVAR_RENT_PUBKEY, Keypair } from "@solana/web3.js";
-import { TOKEN_PROGRAM_ID, createMint, createAccount, mintTo, ASSOCIATED_TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { PublicKey, SystemProgram, SYSVAR_RENT_PUBKEY, Keypair, Transaction, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  ExtensionType,
+  createMint,
+  createAccount,
+  mintTo,
+  getMintLen,
+  getAssociatedTokenAddressSync,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+} from "@solana/spl-token";
 const { assert } = require("chai");
 
 describe("simple_escrow", () => {
@@ -14,12 +26,31 @@ describe("simple_escrow", () => {
   let mint: PublicKey;
   let initializerTokenAccount: PublicKey;
   let receiverTokenAccount: PublicKey;
-  let escrowKeypair: Keypair;
+  let escrow: PublicKey;
   let vaultAuthority: PublicKey;
   let vault: PublicKey;
   let receiver: Keypair;
 
   const escrowAmount = new anchor.BN(100000000); // 100 tokens
+  const escrowSeed = new anchor.BN(1);
+
+  // Escrows are PDAs derived from the initializer and a u64 seed
+  const findEscrowAddress = (seed: anchor.BN) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), provider.publicKey.toBuffer(), seed.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+  // Escrow terms with no deadline, schedule or arbiter unless overridden
+  const escrowTerms = (recipients: { address: PublicKey; shareBps: number }[], overrides = {}) => ({
+    recipients,
+    deadline: new anchor.BN(0),
+    releaseStart: new anchor.BN(0),
+    cliff: new anchor.BN(0),
+    releaseEnd: new anchor.BN(0),
+    arbiter: null,
+    ...overrides,
+  });
 
   before(async () => {
     // Create mint
@@ -60,12 +91,12 @@ describe("simple_escrow", () => {
       1000000000 // 1000 tokens
     );
 
-    // Generate escrow keypair
-    escrowKeypair = Keypair.generate();
+    // Derive escrow PDA
+    escrow = findEscrowAddress(escrowSeed);
 
     // Derive PDA for vault authority
     [vaultAuthority] = await PublicKey.findProgramAddress(
-      [Buffer.from("vault"), escrowKeypair.publicKey.toBuffer()],
+      [Buffer.from("vault"), escrow.toBuffer()],
       program.programId
     );
 
@@ -78,9 +109,9 @@ describe("simple_escrow", () => {
 
   it("Initializes escrow", async () => {
     await program.methods
-      .initializeEscrow(escrowAmount, receiver.publicKey)
+      .initializeEscrow(escrowSeed, escrowAmount, escrowTerms([{ address: receiver.publicKey, shareBps: 10000 }]), null)
       .accounts({
-        escrow: escrowKeypair.publicKey,
+        escrow: escrow,
         initializer: provider.publicKey,
         initializerTokenAccount: initializerTokenAccount,
         vaultAuthority: vaultAuthority,
@@ -91,15 +122,16 @@ describe("simple_escrow", () => {
         associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
         rent: SYSVAR_RENT_PUBKEY,
       })
-      .signers([escrowKeypair])
       .rpc();
 
     // Verify escrow state
-    const escrowAccount = await program.account.escrow.fetch(escrowKeypair.publicKey);
+    const escrowAccount = await program.account.escrow.fetch(escrow);
     assert.ok(escrowAccount.initializer.equals(provider.publicKey));
-    assert.ok(escrowAccount.receiver.equals(receiver.publicKey));
+    assert.equal(escrowAccount.recipients.length, 1);
+    assert.ok(escrowAccount.recipients[0].address.equals(receiver.publicKey));
     assert.ok(escrowAccount.mint.equals(mint));
     assert.equal(escrowAccount.amount.toString(), escrowAmount.toString());
+    assert.equal(escrowAccount.seed.toString(), escrowSeed.toString());
 
     // Verify tokens were transferred to vault
     const vaultBalance = await provider.connection.getTokenAccountBalance(vault);
@@ -109,16 +141,17 @@ describe("simple_escrow", () => {
   it("Claims escrow", async () => {
     // Get initial balances
     const initialReceiverBalance = await provider.connection.getTokenAccountBalance(receiverTokenAccount);
-    const initialVaultBalance = await provider.connection.getTokenAccountBalance(vault);
 
     await program.methods
       .claimEscrow()
       .accounts({
-        escrow: escrowKeypair.publicKey,
+        escrow: escrow,
         vaultAuthority: vaultAuthority,
         vault: vault,
+        mint: mint,
         receiver: receiver.publicKey,
         receiverTokenAccount: receiverTokenAccount,
+        initializer: provider.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
       .signers([receiver])
@@ -126,15 +159,984 @@ describe("simple_escrow", () => {
 
     // Verify balances after claim
     const finalReceiverBalance = await provider.connection.getTokenAccountBalance(receiverTokenAccount);
-    const finalVaultBalance = await provider.connection.getTokenAccountBalance(vault);
 
     assert.equal(
       finalReceiverBalance.value.amount,
       (BigInt(initialReceiverBalance.value.amount) + BigInt(escrowAmount.toString())).toString()
     );
+
+    // Escrow is single-use: vault and escrow accounts are closed
+    assert.isNull(await provider.connection.getAccountInfo(vault));
+    assert.isNull(await provider.connection.getAccountInfo(escrow));
+  });
+
+  it("Cancels escrow and refunds the initializer", async () => {
+    const cancelSeed = new anchor.BN(2);
+    const cancelEscrow = findEscrowAddress(cancelSeed);
+    const [cancelVaultAuthority] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), cancelEscrow.toBuffer()],
+      program.programId
+    );
+    const cancelVault = await anchor.utils.token.associatedAddress({
+      mint: mint,
+      owner: cancelVaultAuthority
+    });
+
+    await program.methods
+      .initializeEscrow(cancelSeed, escrowAmount, escrowTerms([{ address: receiver.publicKey, shareBps: 10000 }]), null)
+      .accounts({
+        escrow: cancelEscrow,
+        initializer: provider.publicKey,
+        initializerTokenAccount: initializerTokenAccount,
+        vaultAuthority: cancelVaultAuthority,
+        vault: cancelVault,
+        mint: mint,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    const balanceBeforeCancel = await provider.connection.getTokenAccountBalance(initializerTokenAccount);
+
+    await program.methods
+      .cancelEscrow()
+      .accounts({
+        escrow: cancelEscrow,
+        vaultAuthority: cancelVaultAuthority,
+        vault: cancelVault,
+        mint: mint,
+        initializer: provider.publicKey,
+        initializerTokenAccount: initializerTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    // Tokens are refunded and both accounts are closed
+    const balanceAfterCancel = await provider.connection.getTokenAccountBalance(initializerTokenAccount);
+    assert.equal(
+      balanceAfterCancel.value.amount,
+      (BigInt(balanceBeforeCancel.value.amount) + BigInt(escrowAmount.toString())).toString()
+    );
+    assert.isNull(await provider.connection.getAccountInfo(cancelVault));
+    assert.isNull(await provider.connection.getAccountInfo(cancelEscrow));
+  });
+
+  it("Only lets the initializer cancel, and only after the deadline", async () => {
+    const deadlineSeed = new anchor.BN(13);
+    const deadlineEscrow = findEscrowAddress(deadlineSeed);
+    const [deadlineVaultAuthority] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), deadlineEscrow.toBuffer()],
+      program.programId
+    );
+    const deadlineVault = await anchor.utils.token.associatedAddress({
+      mint: mint,
+      owner: deadlineVaultAuthority
+    });
+
+    const deadline = new anchor.BN(Math.floor(Date.now() / 1000) + 3600);
+    await program.methods
+      .initializeEscrow(
+        deadlineSeed, escrowAmount, escrowTerms([{ address: receiver.publicKey, shareBps: 10000 }], { deadline }), null
+      )
+      .accounts({
+        escrow: deadlineEscrow,
+        initializer: provider.publicKey,
+        initializerTokenAccount: initializerTokenAccount,
+        vaultAuthority: deadlineVaultAuthority,
+        vault: deadlineVault,
+        mint: mint,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    // The escrow is derived from its initializer, so nobody else can pose as one
+    try {
+      await program.methods
+        .cancelEscrow()
+        .accounts({
+          escrow: deadlineEscrow,
+          vaultAuthority: deadlineVaultAuthority,
+          vault: deadlineVault,
+          mint: mint,
+          initializer: receiver.publicKey,
+          initializerTokenAccount: receiverTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([receiver])
+        .rpc();
+      assert.fail("Cancel by a non-initializer should fail");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "ConstraintSeeds");
+    }
+
+    try {
+      await program.methods
+        .cancelEscrow()
+        .accounts({
+          escrow: deadlineEscrow,
+          vaultAuthority: deadlineVaultAuthority,
+          vault: deadlineVault,
+          mint: mint,
+          initializer: provider.publicKey,
+          initializerTokenAccount: initializerTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      assert.fail("Cancel before the deadline should fail");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "DeadlineNotReached");
+    }
+
+    // The deadline only holds back the initializer: the recipient can still claim
+    await program.methods
+      .claimEscrow()
+      .accounts({
+        escrow: deadlineEscrow,
+        vaultAuthority: deadlineVaultAuthority,
+        vault: deadlineVault,
+        mint: mint,
+        receiver: receiver.publicKey,
+        receiverTokenAccount: receiverTokenAccount,
+        initializer: provider.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([receiver])
+      .rpc();
+    assert.isNull(await provider.connection.getAccountInfo(deadlineEscrow));
+  });
+
+  it("Exchanges escrowed tokens for the requested tokens", async () => {
+    const takerAmount = new anchor.BN(50000000); // 50 tokens
+
+    // Mint the receiver has to pay with
+    const takerMint = await createMint(
+      provider.connection,
+      (provider.wallet as any).payer,
+      provider.publicKey,
+      null,
+      6
+    );
+    const receiverTakerTokenAccount = await createAccount(
+      provider.connection,
+      (provider.wallet as any).payer,
+      takerMint,
+      receiver.publicKey
+    );
+    const initializerTakerTokenAccount = await createAccount(
+      provider.connection,
+      (provider.wallet as any).payer,
+      takerMint,
+      provider.publicKey
+    );
+    await mintTo(
+      provider.connection,
+      (provider.wallet as any).payer,
+      takerMint,
+      receiverTakerTokenAccount,
+      provider.publicKey,
+      takerAmount.toNumber()
+    );
+
+    const swapSeed = new anchor.BN(3);
+    const swapEscrow = findEscrowAddress(swapSeed);
+    const [swapVaultAuthority] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), swapEscrow.toBuffer()],
+      program.programId
+    );
+    const swapVault = await anchor.utils.token.associatedAddress({
+      mint: mint,
+      owner: swapVaultAuthority
+    });
+
+    await program.methods
+      .initializeEscrow(swapSeed, escrowAmount, escrowTerms([{ address: receiver.publicKey, shareBps: 10000 }]), { takerMint, takerAmount })
+      .accounts({
+        escrow: swapEscrow,
+        initializer: provider.publicKey,
+        initializerTokenAccount: initializerTokenAccount,
+        vaultAuthority: swapVaultAuthority,
+        vault: swapVault,
+        mint: mint,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    // A swap escrow cannot be claimed for free
+    try {
+      await program.methods
+        .claimEscrow()
+        .accounts({
+          escrow: swapEscrow,
+          vaultAuthority: swapVaultAuthority,
+          vault: swapVault,
+          mint: mint,
+          receiver: receiver.publicKey,
+          receiverTokenAccount: receiverTokenAccount,
+          initializer: provider.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([receiver])
+        .rpc();
+      assert.fail("Claim should require an exchange");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "ExchangeRequired");
+    }
+
+    const initialReceiverBalance = await provider.connection.getTokenAccountBalance(receiverTokenAccount);
+
+    await program.methods
+      .exchange()
+      .accounts({
+        escrow: swapEscrow,
+        vaultAuthority: swapVaultAuthority,
+        vault: swapVault,
+        mint: mint,
+        receiver: receiver.publicKey,
+        receiverTokenAccount: receiverTokenAccount,
+        receiverTakerTokenAccount: receiverTakerTokenAccount,
+        initializer: provider.publicKey,
+        initializerTakerTokenAccount: initializerTakerTokenAccount,
+        takerMint: takerMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        takerTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([receiver])
+      .rpc();
+
+    // Both legs settled atomically
+    const finalReceiverBalance = await provider.connection.getTokenAccountBalance(receiverTokenAccount);
+    const initializerTakerBalance = await provider.connection.getTokenAccountBalance(initializerTakerTokenAccount);
+    assert.equal(
+      finalReceiverBalance.value.amount,
+      (BigInt(initialReceiverBalance.value.amount) + BigInt(escrowAmount.toString())).toString()
+    );
+    assert.equal(initializerTakerBalance.value.amount, takerAmount.toString());
+    assert.isNull(await provider.connection.getAccountInfo(swapVault));
+    assert.isNull(await provider.connection.getAccountInfo(swapEscrow));
+  });
+
+  it("Releases a vesting escrow linearly through partial claims", async () => {
+    const vestingSeed = new anchor.BN(4);
+    const vestingEscrow = findEscrowAddress(vestingSeed);
+    const [vestingVaultAuthority] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), vestingEscrow.toBuffer()],
+      program.programId
+    );
+    const vestingVault = await anchor.utils.token.associatedAddress({
+      mint: mint,
+      owner: vestingVaultAuthority
+    });
+
+    // Halfway through a schedule whose cliff has already passed
+    const now = Math.floor(Date.now() / 1000);
+    const releaseStart = new anchor.BN(now - 1000);
+    const cliff = new anchor.BN(now - 500);
+    const releaseEnd = new anchor.BN(now + 1000);
+
+    await program.methods
+      .initializeEscrow(
+        vestingSeed, escrowAmount, escrowTerms([{ address: receiver.publicKey, shareBps: 10000 }], { releaseStart, cliff, releaseEnd }), null
+      )
+      .accounts({
+        escrow: vestingEscrow,
+        initializer: provider.publicKey,
+        initializerTokenAccount: initializerTokenAccount,
+        vaultAuthority: vestingVaultAuthority,
+        vault: vestingVault,
+        mint: mint,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    const initialReceiverBalance = await provider.connection.getTokenAccountBalance(receiverTokenAccount);
+
+    await program.methods
+      .claimEscrow()
+      .accounts({
+        escrow: vestingEscrow,
+        vaultAuthority: vestingVaultAuthority,
+        vault: vestingVault,
+        mint: mint,
+        receiver: receiver.publicKey,
+        receiverTokenAccount: receiverTokenAccount,
+        initializer: provider.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([receiver])
+      .rpc();
+
+    // Only the vested portion is released and the escrow stays open
+    const escrowAccount = await program.account.escrow.fetch(vestingEscrow);
+    const finalReceiverBalance = await provider.connection.getTokenAccountBalance(receiverTokenAccount);
+    const received = BigInt(finalReceiverBalance.value.amount) - BigInt(initialReceiverBalance.value.amount);
+
+    assert.equal(received.toString(), escrowAccount.amountClaimed.toString());
+    assert.ok(escrowAccount.amountClaimed.gtn(0));
+    assert.ok(escrowAccount.amountClaimed.lt(escrowAmount));
+
+    const vaultBalance = await provider.connection.getTokenAccountBalance(vestingVault);
+    assert.equal(
+      vaultBalance.value.amount,
+      escrowAmount.sub(escrowAccount.amountClaimed).toString()
+    );
+  });
+
+  it("Rejects claims before the cliff", async () => {
+    const cliffSeed = new anchor.BN(5);
+    const cliffEscrow = findEscrowAddress(cliffSeed);
+    const [cliffVaultAuthority] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), cliffEscrow.toBuffer()],
+      program.programId
+    );
+    const cliffVault = await anchor.utils.token.associatedAddress({
+      mint: mint,
+      owner: cliffVaultAuthority
+    });
+
+    const now = Math.floor(Date.now() / 1000);
+
+    await program.methods
+      .initializeEscrow(
+        cliffSeed,
+        escrowAmount,
+        escrowTerms([{ address: receiver.publicKey, shareBps: 10000 }], {
+          releaseStart: new anchor.BN(now - 100),
+          cliff: new anchor.BN(now + 1000),
+          releaseEnd: new anchor.BN(now + 2000),
+        }),
+        null
+      )
+      .accounts({
+        escrow: cliffEscrow,
+        initializer: provider.publicKey,
+        initializerTokenAccount: initializerTokenAccount,
+        vaultAuthority: cliffVaultAuthority,
+        vault: cliffVault,
+        mint: mint,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .claimEscrow()
+        .accounts({
+          escrow: cliffEscrow,
+          vaultAuthority: cliffVaultAuthority,
+          vault: cliffVault,
+          mint: mint,
+          receiver: receiver.publicKey,
+          receiverTokenAccount: receiverTokenAccount,
+          initializer: provider.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([receiver])
+        .rpc();
+      assert.fail("Claim should fail before the cliff");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "NothingToClaim");
+    }
+  });
+
+  it("Refunds only the unvested part when cancelling a vesting escrow", async () => {
+    const vestingSeed = new anchor.BN(11);
+    const vestingEscrow = findEscrowAddress(vestingSeed);
+    const [vestingVaultAuthority] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), vestingEscrow.toBuffer()],
+      program.programId
+    );
+    const vestingVault = await anchor.utils.token.associatedAddress({
+      mint: mint,
+      owner: vestingVaultAuthority
+    });
+
+    // Roughly half of the schedule has vested
+    const now = Math.floor(Date.now() / 1000);
+    await program.methods
+      .initializeEscrow(
+        vestingSeed,
+        escrowAmount,
+        escrowTerms([{ address: receiver.publicKey, shareBps: 10000 }], {
+          releaseStart: new anchor.BN(now - 1000),
+          cliff: new anchor.BN(now - 500),
+          releaseEnd: new anchor.BN(now + 1000),
+        }),
+        null
+      )
+      .accounts({
+        escrow: vestingEscrow,
+        initializer: provider.publicKey,
+        initializerTokenAccount: initializerTokenAccount,
+        vaultAuthority: vestingVaultAuthority,
+        vault: vestingVault,
+        mint: mint,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    const initializerBefore = await provider.connection.getTokenAccountBalance(initializerTokenAccount);
+    await program.methods
+      .cancelEscrow()
+      .accounts({
+        escrow: vestingEscrow,
+        vaultAuthority: vestingVaultAuthority,
+        vault: vestingVault,
+        mint: mint,
+        initializer: provider.publicKey,
+        initializerTokenAccount: initializerTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    // The initializer gets the unvested part back; the vested part stays put
+    const escrowAccount = await program.account.escrow.fetch(vestingEscrow);
+    const vested = escrowAccount.amount;
+    assert.ok(vested.gtn(0) && vested.lt(escrowAmount));
+    const initializerAfter = await provider.connection.getTokenAccountBalance(initializerTokenAccount);
+    assert.equal(
+      (BigInt(initializerAfter.value.amount) - BigInt(initializerBefore.value.amount)).toString(),
+      escrowAmount.sub(vested).toString()
+    );
+    const vaultBalance = await provider.connection.getTokenAccountBalance(vestingVault);
+    assert.equal(vaultBalance.value.amount, vested.toString());
+
+    // The receiver can still claim everything that vested before the cancel
+    const receiverBefore = await provider.connection.getTokenAccountBalance(receiverTokenAccount);
+    await program.methods
+      .claimEscrow()
+      .accounts({
+        escrow: vestingEscrow,
+        vaultAuthority: vestingVaultAuthority,
+        vault: vestingVault,
+        mint: mint,
+        receiver: receiver.publicKey,
+        receiverTokenAccount: receiverTokenAccount,
+        initializer: provider.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([receiver])
+      .rpc();
+
+    const receiverAfter = await provider.connection.getTokenAccountBalance(receiverTokenAccount);
+    assert.equal(
+      (BigInt(receiverAfter.value.amount) - BigInt(receiverBefore.value.amount)).toString(),
+      vested.toString()
+    );
+    assert.isNull(await provider.connection.getAccountInfo(vestingVault));
+    assert.isNull(await provider.connection.getAccountInfo(vestingEscrow));
+  });
+
+  it("Lets the arbiter split a disputed escrow", async () => {
+    const arbiter = Keypair.generate();
+    const disputeSeed = new anchor.BN(6);
+    const disputeEscrow = findEscrowAddress(disputeSeed);
+    const [disputeVaultAuthority] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), disputeEscrow.toBuffer()],
+      program.programId
+    );
+    const disputeVault = await anchor.utils.token.associatedAddress({
+      mint: mint,
+      owner: disputeVaultAuthority
+    });
+
+    await program.methods
+      .initializeEscrow(
+        disputeSeed, escrowAmount, escrowTerms([{ address: receiver.publicKey, shareBps: 10000 }], { arbiter: arbiter.publicKey }), null
+      )
+      .accounts({
+        escrow: disputeEscrow,
+        initializer: provider.publicKey,
+        initializerTokenAccount: initializerTokenAccount,
+        vaultAuthority: disputeVaultAuthority,
+        vault: disputeVault,
+        mint: mint,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    await program.methods
+      .raiseDispute()
+      .accounts({
+        escrow: disputeEscrow,
+        party: receiver.publicKey,
+      })
+      .signers([receiver])
+      .rpc();
+
+    // Claims are blocked while the dispute is open
+    try {
+      await program.methods
+        .claimEscrow()
+        .accounts({
+          escrow: disputeEscrow,
+          vaultAuthority: disputeVaultAuthority,
+          vault: disputeVault,
+          mint: mint,
+          receiver: receiver.publicKey,
+          receiverTokenAccount: receiverTokenAccount,
+          initializer: provider.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([receiver])
+        .rpc();
+      assert.fail("Claim should fail while disputed");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "DisputeOpen");
+    }
+
+    const initialReceiverBalance = await provider.connection.getTokenAccountBalance(receiverTokenAccount);
+    const initialInitializerBalance = await provider.connection.getTokenAccountBalance(initializerTokenAccount);
+
+    // 60% to the receiver, 40% back to the initializer
+    await program.methods
+      .resolveDispute(6000)
+      .accounts({
+        escrow: disputeEscrow,
+        vaultAuthority: disputeVaultAuthority,
+        vault: disputeVault,
+        mint: mint,
+        arbiter: arbiter.publicKey,
+        initializer: provider.publicKey,
+        initializerTokenAccount: initializerTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        { pubkey: receiverTokenAccount, isWritable: true, isSigner: false },
+      ])
+      .signers([arbiter])
+      .rpc();
+
+    const finalReceiverBalance = await provider.connection.getTokenAccountBalance(receiverTokenAccount);
+    const finalInitializerBalance = await provider.connection.getTokenAccountBalance(initializerTokenAccount);
+    const receiverShare = escrowAmount.muln(6000).divn(10000);
+
+    assert.equal(
+      finalReceiverBalance.value.amount,
+      (BigInt(initialReceiverBalance.value.amount) + BigInt(receiverShare.toString())).toString()
+    );
     assert.equal(
-      finalVaultBalance.value.amount,
-      (BigInt(initialVaultBalance.value.amount) - BigInt(escrowAmount.toString())).toString()
+      finalInitializerBalance.value.amount,
+      (BigInt(initialInitializerBalance.value.amount) + BigInt(escrowAmount.sub(receiverShare).toString())).toString()
+    );
+    assert.isNull(await provider.connection.getAccountInfo(disputeEscrow));
+  });
+
+  it("Splits a disputed escrow by what each recipient is still owed", async () => {
+    const arbiter = Keypair.generate();
+    const secondReceiver = Keypair.generate();
+    const secondReceiverTokenAccount = await createAccount(
+      provider.connection,
+      (provider.wallet as any).payer,
+      mint,
+      secondReceiver.publicKey
+    );
+
+    const owedSeed = new anchor.BN(12);
+    const owedEscrow = findEscrowAddress(owedSeed);
+    const [owedVaultAuthority] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), owedEscrow.toBuffer()],
+      program.programId
     );
+    const owedVault = await anchor.utils.token.associatedAddress({
+      mint: mint,
+      owner: owedVaultAuthority
+    });
+
+    const recipients = [
+      { address: receiver.publicKey, shareBps: 7000 },
+      { address: secondReceiver.publicKey, shareBps: 3000 },
+    ];
+    await program.methods
+      .initializeEscrow(owedSeed, escrowAmount, escrowTerms(recipients, { arbiter: arbiter.publicKey }), null)
+      .accounts({
+        escrow: owedEscrow,
+        initializer: provider.publicKey,
+        initializerTokenAccount: initializerTokenAccount,
+        vaultAuthority: owedVaultAuthority,
+        vault: owedVault,
+        mint: mint,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    // The first recipient takes their 70% before the dispute
+    await program.methods
+      .claimEscrow()
+      .accounts({
+        escrow: owedEscrow,
+        vaultAuthority: owedVaultAuthority,
+        vault: owedVault,
+        mint: mint,
+        receiver: receiver.publicKey,
+        receiverTokenAccount: receiverTokenAccount,
+        initializer: provider.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([receiver])
+      .rpc();
+    await program.methods
+      .raiseDispute()
+      .accounts({
+        escrow: owedEscrow,
+        party: secondReceiver.publicKey,
+      })
+      .signers([secondReceiver])
+      .rpc();
+
+    const initialReceiverBalance = await provider.connection.getTokenAccountBalance(receiverTokenAccount);
+    const initialInitializerBalance = await provider.connection.getTokenAccountBalance(initializerTokenAccount);
+
+    // Half of the remaining 30% goes to the recipients, and all of it to the
+    // second one: the first has nothing left to be owed
+    await program.methods
+      .resolveDispute(5000)
+      .accounts({
+        escrow: owedEscrow,
+        vaultAuthority: owedVaultAuthority,
+        vault: owedVault,
+        mint: mint,
+        arbiter: arbiter.publicKey,
+        initializer: provider.publicKey,
+        initializerTokenAccount: initializerTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        { pubkey: receiverTokenAccount, isWritable: true, isSigner: false },
+        { pubkey: secondReceiverTokenAccount, isWritable: true, isSigner: false },
+      ])
+      .signers([arbiter])
+      .rpc();
+
+    const half = escrowAmount.muln(3000).divn(10000).divn(2);
+    const finalReceiverBalance = await provider.connection.getTokenAccountBalance(receiverTokenAccount);
+    const secondReceiverBalance = await provider.connection.getTokenAccountBalance(secondReceiverTokenAccount);
+    const finalInitializerBalance = await provider.connection.getTokenAccountBalance(initializerTokenAccount);
+    assert.equal(finalReceiverBalance.value.amount, initialReceiverBalance.value.amount);
+    assert.equal(secondReceiverBalance.value.amount, half.toString());
+    assert.equal(
+      finalInitializerBalance.value.amount,
+      (BigInt(initialInitializerBalance.value.amount) + BigInt(half.toString())).toString()
+    );
+    assert.isNull(await provider.connection.getAccountInfo(owedEscrow));
+  });
+
+  it("Pays each recipient their share and closes after the last claim", async () => {
+    const secondReceiver = Keypair.generate();
+    const secondReceiverTokenAccount = await createAccount(
+      provider.connection,
+      (provider.wallet as any).payer,
+      mint,
+      secondReceiver.publicKey
+    );
+
+    const splitSeed = new anchor.BN(7);
+    const splitEscrow = findEscrowAddress(splitSeed);
+    const [splitVaultAuthority] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), splitEscrow.toBuffer()],
+      program.programId
+    );
+    const splitVault = await anchor.utils.token.associatedAddress({
+      mint: mint,
+      owner: splitVaultAuthority
+    });
+
+    const recipients = [
+      { address: receiver.publicKey, shareBps: 7000 },
+      { address: secondReceiver.publicKey, shareBps: 3000 },
+    ];
+
+    await program.methods
+      .initializeEscrow(
+        splitSeed, escrowAmount, escrowTerms(recipients), null
+      )
+      .accounts({
+        escrow: splitEscrow,
+        initializer: provider.publicKey,
+        initializerTokenAccount: initializerTokenAccount,
+        vaultAuthority: splitVaultAuthority,
+        vault: splitVault,
+        mint: mint,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    // Someone outside the recipient list can't claim anything
+    const outsider = Keypair.generate();
+    const outsiderTokenAccount = await createAccount(
+      provider.connection,
+      (provider.wallet as any).payer,
+      mint,
+      outsider.publicKey
+    );
+    try {
+      await program.methods
+        .claimEscrow()
+        .accounts({
+          escrow: splitEscrow,
+          vaultAuthority: splitVaultAuthority,
+          vault: splitVault,
+          mint: mint,
+          receiver: outsider.publicKey,
+          receiverTokenAccount: outsiderTokenAccount,
+          initializer: provider.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([outsider])
+        .rpc();
+      assert.fail("Claim by a non-recipient should fail");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "Unauthorized");
+    }
+
+    const initialReceiverBalance = await provider.connection.getTokenAccountBalance(receiverTokenAccount);
+
+    await program.methods
+      .claimEscrow()
+      .accounts({
+        escrow: splitEscrow,
+        vaultAuthority: splitVaultAuthority,
+        vault: splitVault,
+        mint: mint,
+        receiver: receiver.publicKey,
+        receiverTokenAccount: receiverTokenAccount,
+        initializer: provider.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([receiver])
+      .rpc();
+
+    // First recipient gets 70% and the escrow stays open for the second
+    const finalReceiverBalance = await provider.connection.getTokenAccountBalance(receiverTokenAccount);
+    assert.equal(
+      finalReceiverBalance.value.amount,
+      (BigInt(initialReceiverBalance.value.amount) + BigInt(escrowAmount.muln(7000).divn(10000).toString())).toString()
+    );
+    assert.isNotNull(await provider.connection.getAccountInfo(splitEscrow));
+
+    await program.methods
+      .claimEscrow()
+      .accounts({
+        escrow: splitEscrow,
+        vaultAuthority: splitVaultAuthority,
+        vault: splitVault,
+        mint: mint,
+        receiver: secondReceiver.publicKey,
+        receiverTokenAccount: secondReceiverTokenAccount,
+        initializer: provider.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([secondReceiver])
+      .rpc();
+
+    const secondReceiverBalance = await provider.connection.getTokenAccountBalance(secondReceiverTokenAccount);
+    assert.equal(secondReceiverBalance.value.amount, escrowAmount.muln(3000).divn(10000).toString());
+    assert.isNull(await provider.connection.getAccountInfo(splitVault));
+    assert.isNull(await provider.connection.getAccountInfo(splitEscrow));
+  });
+
+  it("Escrows a Token-2022 transfer-fee mint and credits the received amount", async () => {
+    const feeBasisPoints = 100; // 1%
+    const maxFee = BigInt(1_000_000_000);
+
+    // Create a Token-2022 mint with the transfer fee extension
+    const feeMint = Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    const lamports = await provider.connection.getMinimumBalanceForRentExemption(mintLen);
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: provider.publicKey,
+          newAccountPubkey: feeMint.publicKey,
+          space: mintLen,
+          lamports,
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferFeeConfigInstruction(
+          feeMint.publicKey,
+          provider.publicKey,
+          provider.publicKey,
+          feeBasisPoints,
+          maxFee,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializeMintInstruction(feeMint.publicKey, 6, provider.publicKey, null, TOKEN_2022_PROGRAM_ID)
+      ),
+      [feeMint]
+    );
+
+    const payer = (provider.wallet as any).payer;
+    const initializerFeeTokenAccount = await createAccount(
+      provider.connection, payer, feeMint.publicKey, provider.publicKey, undefined, undefined, TOKEN_2022_PROGRAM_ID
+    );
+    const receiverFeeTokenAccount = await createAccount(
+      provider.connection, payer, feeMint.publicKey, receiver.publicKey, undefined, undefined, TOKEN_2022_PROGRAM_ID
+    );
+    await mintTo(
+      provider.connection, payer, feeMint.publicKey, initializerFeeTokenAccount, provider.publicKey,
+      1000000000, [], undefined, TOKEN_2022_PROGRAM_ID
+    );
+
+    const feeSeed = new anchor.BN(8);
+    const feeEscrow = findEscrowAddress(feeSeed);
+    const [feeVaultAuthority] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), feeEscrow.toBuffer()],
+      program.programId
+    );
+    const feeVault = getAssociatedTokenAddressSync(
+      feeMint.publicKey, feeVaultAuthority, true, TOKEN_2022_PROGRAM_ID
+    );
+
+    await program.methods
+      .initializeEscrow(
+        feeSeed, escrowAmount, escrowTerms([{ address: receiver.publicKey, shareBps: 10000 }]), null
+      )
+      .accounts({
+        escrow: feeEscrow,
+        initializer: provider.publicKey,
+        initializerTokenAccount: initializerFeeTokenAccount,
+        vaultAuthority: feeVaultAuthority,
+        vault: feeVault,
+        mint: feeMint.publicKey,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    // The escrow records what actually reached the vault, net of the fee
+    const expectedFee = escrowAmount.muln(feeBasisPoints).divn(10000);
+    const escrowAccount = await program.account.escrow.fetch(feeEscrow);
+    const vaultBalance = await provider.connection.getTokenAccountBalance(feeVault);
+    assert.equal(escrowAccount.amount.toString(), escrowAmount.sub(expectedFee).toString());
+    assert.equal(vaultBalance.value.amount, escrowAccount.amount.toString());
+
+    await program.methods
+      .claimEscrow()
+      .accounts({
+        escrow: feeEscrow,
+        vaultAuthority: feeVaultAuthority,
+        vault: feeVault,
+        mint: feeMint.publicKey,
+        receiver: receiver.publicKey,
+        receiverTokenAccount: receiverFeeTokenAccount,
+        initializer: provider.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([receiver])
+      .rpc();
+
+    // Withheld fees are harvested so the vault can still be closed
+    const receiverBalance = await provider.connection.getTokenAccountBalance(receiverFeeTokenAccount);
+    assert.ok(BigInt(receiverBalance.value.amount) > BigInt(0));
+    assert.isNull(await provider.connection.getAccountInfo(feeVault));
+    assert.isNull(await provider.connection.getAccountInfo(feeEscrow));
+  });
+
+  it("Escrows native SOL without a vault and pays it out on claim", async () => {
+    const solAmount = new anchor.BN(LAMPORTS_PER_SOL / 2);
+    const solSeed = new anchor.BN(9);
+    const solEscrow = findEscrowAddress(solSeed);
+
+    await program.methods
+      .initializeSolEscrow(
+        solSeed, solAmount, escrowTerms([{ address: receiver.publicKey, shareBps: 10000 }])
+      )
+      .accounts({
+        escrow: solEscrow,
+        initializer: provider.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const escrowAccount = await program.account.escrow.fetch(solEscrow);
+    assert.deepEqual(escrowAccount.mode, { sol: {} });
+    assert.equal(escrowAccount.amount.toString(), solAmount.toString());
+
+    const receiverBalanceBefore = await provider.connection.getBalance(receiver.publicKey);
+
+    // No token accounts are needed in SOL mode
+    await program.methods
+      .claimEscrow()
+      .accounts({
+        escrow: solEscrow,
+        vaultAuthority: null,
+        vault: null,
+        mint: null,
+        receiver: receiver.publicKey,
+        receiverTokenAccount: null,
+        initializer: provider.publicKey,
+        tokenProgram: null,
+      })
+      .signers([receiver])
+      .rpc();
+
+    const receiverBalanceAfter = await provider.connection.getBalance(receiver.publicKey);
+    assert.equal(receiverBalanceAfter - receiverBalanceBefore, solAmount.toNumber());
+    assert.isNull(await provider.connection.getAccountInfo(solEscrow));
+  });
+
+  it("Refunds a cancelled SOL escrow to the initializer", async () => {
+    const solAmount = new anchor.BN(LAMPORTS_PER_SOL / 2);
+    const solSeed = new anchor.BN(10);
+    const solEscrow = findEscrowAddress(solSeed);
+
+    await program.methods
+      .initializeSolEscrow(
+        solSeed, solAmount, escrowTerms([{ address: receiver.publicKey, shareBps: 10000 }])
+      )
+      .accounts({
+        escrow: solEscrow,
+        initializer: provider.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const escrowLamports = (await provider.connection.getAccountInfo(solEscrow)).lamports;
+    const balanceBefore = await provider.connection.getBalance(provider.publicKey);
+
+    await program.methods
+      .cancelEscrow()
+      .accounts({
+        escrow: solEscrow,
+        vaultAuthority: null,
+        vault: null,
+        mint: null,
+        initializer: provider.publicKey,
+        initializerTokenAccount: null,
+        tokenProgram: null,
+      })
+      .rpc();
+
+    // The escrowed lamports and the escrow's rent come back, minus the tx fee
+    const balanceAfter = await provider.connection.getBalance(provider.publicKey);
+    assert.isAbove(balanceAfter - balanceBefore, escrowLamports - 10000);
+    assert.isNull(await provider.connection.getAccountInfo(solEscrow));
   });
 });
@@ -2,10 +2,15 @@ use anchor_lang::prelude::*;
 
 declare_id!("EFpkThxpS78297Lor9as1hW9pWa3My9kc1k1auiyS4b4");
 
-const POINTS_PER_SOL_PER_DAY: u64 = 100_000;
-const POINTS_PER_SOL_PAYOUT: u64 = 10_000_000_000;
+const DEFAULT_POINTS_PER_SOL_PER_DAY: u64 = 100_000;
+const DEFAULT_POINTS_PER_SOL_PAYOUT: u64 = 10_000_000_000;
 const SECONDS_PER_DAY: u64 = 86_400;
 const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+const DEFAULT_LOW_BALANCE_WARNING: u64 = 25 * LAMPORTS_PER_SOL;
+const DEFAULT_LOW_BALANCE_CRITICAL: u64 = 10 * LAMPORTS_PER_SOL;
+const MAX_MULTISIG_SIGNERS: usize = 5;
+/// `Treasury::points_per_lamport` is scaled by this so it accumulates without rounding
+const POINTS_SCALE: u128 = LAMPORTS_PER_SOL as u128 * SECONDS_PER_DAY as u128;
 
 #[program]
 pub mod staking_contract {
@@ -16,9 +21,21 @@ pub mod staking_contract {
     pub fn initialize_treasury(ctx: Context<InitializeTreasury>) -> Result<()> {
         let treasury = &mut ctx.accounts.treasury;
         treasury.admin = ctx.accounts.admin.key();
+        treasury.pending_admin = None;
+        treasury.multisig_signers = Vec::new();
+        treasury.multisig_threshold = 0;
+        treasury.proposal_count = 0;
         treasury.bump = ctx.bumps.treasury;
         treasury.total_funded = 0;
         treasury.total_paid_out = 0;
+        treasury.paused = false;
+        treasury.pause_reason = 0;
+        treasury.points_per_sol_per_day = DEFAULT_POINTS_PER_SOL_PER_DAY;
+        treasury.points_per_sol_payout = DEFAULT_POINTS_PER_SOL_PAYOUT;
+        treasury.points_per_lamport = 0;
+        treasury.last_accrual_time = Clock::get()?.unix_timestamp;
+        treasury.low_balance_warning = DEFAULT_LOW_BALANCE_WARNING;
+        treasury.low_balance_critical = DEFAULT_LOW_BALANCE_CRITICAL;
         
         msg!("Treasury initialized with admin: {}", treasury.admin);
         Ok(())
@@ -43,7 +60,12 @@ pub mod staking_contract {
             .checked_add(amount)
             .ok_or(StakeError::Overflow)?;
         
-        msg!("Treasury funded with {} lamports. Total funded: {}", amount, treasury.total_funded);
+        emit!(TreasuryFunded {
+            admin: ctx.accounts.admin.key(),
+            amount,
+            total_funded: treasury.total_funded,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
     /// Creates a new PDA account for a user where their staking data will be stored
@@ -56,6 +78,7 @@ pub mod staking_contract {
         pda_account.last_update_time = clock.unix_timestamp;
         pda_account.bump = ctx.bumps.pda_account;
         pda_account.total_points = 0;
+        pda_account.points_per_lamport_paid = 0;
 
         msg!("PDA account created successfully for user: {}", pda_account.owner);
         Ok(())
@@ -66,8 +89,9 @@ pub mod staking_contract {
 
         let pda_account = &mut ctx.accounts.pda_account;
         let clock = Clock::get()?;
+        let points_before = pda_account.total_points;
 
-        update_points(pda_account, clock.unix_timestamp)?;
+        update_points(pda_account, &mut ctx.accounts.treasury, clock.unix_timestamp)?;
 
         let cpi_context = CpiContext::new(
             ctx.accounts.system_program.to_account_info(),
@@ -84,12 +108,14 @@ pub mod staking_contract {
             .checked_add(amount)
             .ok_or(StakeError::Overflow)?;
 
-        msg!(
-            "Staked {} lamports. Total staked: {}, Total points: {}",
+        emit!(Staked {
+            user: ctx.accounts.user.key(),
             amount,
-            pda_account.staked_amount,
-            pda_account.total_points
-        );
+            staked_amount: pda_account.staked_amount,
+            points_before,
+            points_after: pda_account.total_points,
+            timestamp: clock.unix_timestamp,
+        });
         Ok(())
     }
     /// Unstakes lamports from the user’s PDA account and updates their reward points
@@ -104,7 +130,8 @@ pub mod staking_contract {
             StakeError::InsufficientStake
         );
 
-        update_points(pda_account, clock.unix_timestamp)?;
+        let points_before = pda_account.total_points;
+        update_points(pda_account, &mut ctx.accounts.treasury, clock.unix_timestamp)?;
 
         **pda_account.to_account_info().try_borrow_mut_lamports()? -= amount;
         **ctx.accounts.user.to_account_info().try_borrow_mut_lamports()? += amount;
@@ -114,12 +141,14 @@ pub mod staking_contract {
             .checked_sub(amount)
             .ok_or(StakeError::Underflow)?;
 
-        msg!(
-            "Unstaked {} lamports. Remaining staked: {}, Total points: {}",
+        emit!(Unstaked {
+            user: ctx.accounts.user.key(),
             amount,
-            pda_account.staked_amount,
-            pda_account.total_points
-        );
+            staked_amount: pda_account.staked_amount,
+            points_before,
+            points_after: pda_account.total_points,
+            timestamp: clock.unix_timestamp,
+        });
 
         Ok(())
     }
@@ -127,17 +156,24 @@ pub mod staking_contract {
     pub fn claim_points(ctx: Context<ClaimPoints>) -> Result<()> {
         let pda_account = &mut ctx.accounts.pda_account;
         let clock = Clock::get()?;
+        let points_before = pda_account.total_points;
         
-        update_points(pda_account, clock.unix_timestamp)?;
+        update_points(pda_account, &mut ctx.accounts.treasury, clock.unix_timestamp)?;
         
         let claimable_points = pda_account.total_points;
         
         require!(claimable_points > 0, StakeError::NoPointsToClaim);
         
-        msg!("User claimed {} points", claimable_points);
-        
         pda_account.total_points = 0;
         
+        emit!(PointsClaimed {
+            user: ctx.accounts.user.key(),
+            points_claimed: claimable_points,
+            points_before,
+            points_after: pda_account.total_points,
+            timestamp: clock.unix_timestamp,
+        });
+        
         Ok(())
     }
     /// Converts user’s points into SOL, paid from treasury (if enough funds exist)
@@ -151,9 +187,10 @@ pub mod staking_contract {
         let treasury = &mut ctx.accounts.treasury;
         let clock = Clock::get()?;
         
-        require!(!treasury.paused, StakeError::ConversionsPaused);
+        ensure_conversions_enabled(treasury)?;
         
-        update_points(pda_account, clock.unix_timestamp)?;
+        let points_before = pda_account.total_points;
+        update_points(pda_account, treasury, clock.unix_timestamp)?;
         
         require!(
             pda_account.total_points >= points_to_convert,
@@ -163,7 +200,7 @@ pub mod staking_contract {
         let sol_payout = points_to_convert
             .checked_mul(LAMPORTS_PER_SOL)
             .ok_or(StakeError::Overflow)?
-            .checked_div(POINTS_PER_SOL_PAYOUT)
+            .checked_div(treasury.points_per_sol_payout)
             .ok_or(StakeError::DivisionByZero)?;
         
         require!(sol_payout > 0, StakeError::InsufficientPointsForPayout);
@@ -188,97 +225,387 @@ pub mod staking_contract {
             .checked_sub(points_to_convert)
             .ok_or(StakeError::Underflow)?;
         
-        msg!(
-            "Converted {} points to {} SOL ({} lamports). Remaining points: {}",
-            points_to_convert,
-            sol_payout as f64 / LAMPORTS_PER_SOL as f64,
-            sol_payout,
-            pda_account.total_points
-        );
+        emit!(PointsConverted {
+            user: ctx.accounts.user.key(),
+            points_converted: points_to_convert,
+            lamports_paid: sol_payout,
+            points_before,
+            points_after: pda_account.total_points,
+            timestamp: clock.unix_timestamp,
+        });
     
         let remaining_balance = treasury.to_account_info().lamports();
-        let remaining_sol = remaining_balance as f64 / LAMPORTS_PER_SOL as f64;
-    
-        if remaining_sol < 10.0 {
-            msg!("🚨 TREASURY ALERT: Only {:.1} SOL remaining! Refund urgently!", remaining_sol);
-        } else if remaining_sol < 25.0 {
-            msg!("⚠️  TREASURY WARNING: {:.1} SOL remaining. Consider refunding soon.", remaining_sol);
+        if remaining_balance < treasury.low_balance_warning {
+            emit!(TreasuryLowBalance {
+                balance: remaining_balance,
+                warning_threshold: treasury.low_balance_warning,
+                critical_threshold: treasury.low_balance_critical,
+                critical: remaining_balance < treasury.low_balance_critical,
+                timestamp: clock.unix_timestamp,
+            });
         }
         
         Ok(())
     }
-    /// Shows how many points the user currently has, without mutating state
-    pub fn get_points(ctx: Context<GetPoints>) -> Result<()> {
+    /// Returns how many points the user currently has, without mutating state.
+    /// Simulate it and decode the `PointsInfo` return data.
+    pub fn get_points(ctx: Context<GetPoints>) -> Result<PointsInfo> {
         let pda_account = &ctx.accounts.pda_account;
         let clock = Clock::get()?;
         
         let time_elapsed = clock.unix_timestamp.checked_sub(pda_account.last_update_time)
             .ok_or(StakeError::InvalidTimestamp)? as u64;
         
-        let new_points = if pda_account.staked_amount > 0 && time_elapsed > 0 {
-            calculate_points_earned(pda_account.staked_amount, time_elapsed)?
-        } else {
-            0
-        };
+        let points_per_lamport = current_points_per_lamport(&ctx.accounts.treasury, clock.unix_timestamp)?;
+        let new_points = calculate_points_earned(
+            pda_account.staked_amount,
+            points_per_lamport.saturating_sub(pda_account.points_per_lamport_paid),
+        )?;
         
         let current_total_points = pda_account.total_points.checked_add(new_points)
             .ok_or(StakeError::Overflow)?;
         
-        msg!(
-            "Current points: {}, Staked amount: {} SOL, Time since last update: {} seconds", 
-            current_total_points,
-            pda_account.staked_amount as f64 / LAMPORTS_PER_SOL as f64,
-            time_elapsed
-        );
-        
-        Ok(())
+        Ok(PointsInfo {
+            points: current_total_points,
+            staked_amount: pda_account.staked_amount,
+            last_update_time: pda_account.last_update_time,
+            seconds_since_update: time_elapsed,
+        })
     }
-    /// Displays treasury info like balance, available funds, funded and paid out totals
-    pub fn get_treasury_info(ctx: Context<GetTreasuryInfo>) -> Result<()> {
+    /// Returns treasury info like balance, available funds, funded and paid out totals.
+    /// Simulate it and decode the `TreasuryInfo` return data.
+    pub fn get_treasury_info(ctx: Context<GetTreasuryInfo>) -> Result<TreasuryInfo> {
         let treasury = &ctx.accounts.treasury;
         let balance = treasury.to_account_info().lamports();
         let rent_exemption = Rent::get()?.minimum_balance(treasury.to_account_info().data_len());
         let available_balance = balance.checked_sub(rent_exemption).unwrap_or(0);
         
+        Ok(TreasuryInfo {
+            balance,
+            available_balance,
+            total_funded: treasury.total_funded,
+            total_paid_out: treasury.total_paid_out,
+        })
+    }
+    /// Admin-only: pauses conversion of points → SOL, recording why
+    pub fn pause_conversions(ctx: Context<AdminOnly>, reason: u8) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        require!(treasury.multisig_threshold == 0, StakeError::MultisigRequired);
+
+        update_paused(treasury, ctx.accounts.admin.key(), true, reason)?;
+
+        msg!("⏸️ Point conversions PAUSED by admin (reason code: {})", reason);
+        Ok(())
+    }
+    /// Admin-only: resumes conversion of points → SOL and clears the pause reason
+    pub fn unpause_conversions(ctx: Context<AdminOnly>) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        require!(treasury.multisig_threshold == 0, StakeError::MultisigRequired);
+
+        update_paused(treasury, ctx.accounts.admin.key(), false, 0)?;
+
+        msg!("▶️ Point conversions RESUMED by admin");
+        Ok(())
+    }
+    /// Admin-only: nominates `new_admin`, who takes over once they call `accept_admin`.
+    /// Proposing again replaces the pending nomination.
+    pub fn propose_admin(ctx: Context<AdminOnly>, new_admin: Pubkey) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.pending_admin = Some(new_admin);
+
+        emit!(AdminProposed {
+            admin: treasury.admin,
+            pending_admin: new_admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+    /// Completes a handover: the proposed admin signs to take over the treasury
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        let previous_admin = treasury.admin;
+        treasury.admin = ctx.accounts.new_admin.key();
+        treasury.pending_admin = None;
+
+        emit!(AdminTransferred {
+            previous_admin,
+            new_admin: treasury.admin,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+    /// Admin-only: gives up admin rights for good. Funding is impossible
+    /// afterwards, as are pausing and rate changes unless a multisig is set.
+    pub fn renounce_admin(ctx: Context<AdminOnly>) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        let previous_admin = treasury.admin;
+        treasury.admin = Pubkey::default();
+        treasury.pending_admin = None;
+
+        emit!(AdminTransferred {
+            previous_admin,
+            new_admin: Pubkey::default(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+    /// Admin-only: sets the treasury balances (in lamports) below which conversions
+    /// emit `TreasuryLowBalance`, as a warning or as critical
+    pub fn set_low_balance_thresholds(
+        ctx: Context<AdminOnly>,
+        warning: u64,
+        critical: u64,
+    ) -> Result<()> {
+        require!(critical <= warning, StakeError::InvalidThresholds);
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.low_balance_warning = warning;
+        treasury.low_balance_critical = critical;
+
+        msg!("Low balance thresholds: warning {} lamports, critical {} lamports", warning, critical);
+        Ok(())
+    }
+    /// Admin-only: changes the emission and payout rates. Time already elapsed
+    /// keeps the old emission rate; the new one applies from now on.
+    pub fn set_rates(
+        ctx: Context<AdminOnly>,
+        points_per_sol_per_day: u64,
+        points_per_sol_payout: u64,
+    ) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        require!(treasury.multisig_threshold == 0, StakeError::MultisigRequired);
+
+        update_rates(treasury, ctx.accounts.admin.key(), points_per_sol_per_day, points_per_sol_payout)?;
+
         msg!(
-            "Treasury - Balance: {} SOL, Available: {} SOL, Total Funded: {} SOL, Total Paid Out: {} SOL",
-            balance as f64 / LAMPORTS_PER_SOL as f64,
-            available_balance as f64 / LAMPORTS_PER_SOL as f64,
-            treasury.total_funded as f64 / LAMPORTS_PER_SOL as f64,
-            treasury.total_paid_out as f64 / LAMPORTS_PER_SOL as f64
+            "Rates updated: {} points per SOL per day, {} points per SOL payout",
+            points_per_sol_per_day,
+            points_per_sol_payout
         );
-        
         Ok(())
     }
+    /// Admin-only: hands withdrawals, rate changes and pausing over to an
+    /// M-of-N multisig of `signers`. Can only be done once; later changes to the
+    /// signer set go through a `TreasuryAction::SetMultisig` proposal.
+    pub fn set_multisig(ctx: Context<AdminOnly>, signers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        require!(treasury.multisig_threshold == 0, StakeError::MultisigRequired);
+
+        apply_multisig(treasury, signers, threshold)?;
+
+        msg!("Multisig set: {} of {} signers", threshold, treasury.multisig_signers.len());
+        Ok(())
+    }
+    /// Multisig member: proposes `action` in a new proposal PDA, approved by the proposer
+    pub fn create_proposal(ctx: Context<CreateProposal>, action: TreasuryAction) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        let proposal = &mut ctx.accounts.proposal;
+
+        proposal.id = treasury.proposal_count;
+        proposal.proposer = ctx.accounts.proposer.key();
+        proposal.action = action;
+        proposal.approvals = vec![ctx.accounts.proposer.key()];
+        proposal.executed = false;
+        proposal.bump = ctx.bumps.proposal;
+
+        treasury.proposal_count = treasury.proposal_count
+            .checked_add(1)
+            .ok_or(StakeError::Overflow)?;
+
+        emit!(ProposalCreated {
+            proposal_id: proposal.id,
+            proposer: proposal.proposer,
+            action: proposal.action.clone(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+    /// Multisig member: adds their approval to an open proposal
+    pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let member = ctx.accounts.member.key();
+
+        require!(!proposal.approvals.contains(&member), StakeError::AlreadyApproved);
+        // Drop approvals of removed members so the list never outgrows the signer set
+        let signers = &ctx.accounts.treasury.multisig_signers;
+        proposal.approvals.retain(|approver| signers.contains(approver));
+        proposal.approvals.push(member);
+
+        emit!(ProposalApproved {
+            proposal_id: proposal.id,
+            member,
+            approvals: proposal.approvals.len() as u8,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+    /// Multisig member: runs a proposal once approvals from current members reach
+    /// the threshold. A withdrawal pays out to `recipient`, which must match the proposal.
+    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+        let treasury = &mut ctx.accounts.treasury;
+        let proposal = &mut ctx.accounts.proposal;
+        let member = ctx.accounts.member.key();
+
+        // Approvals from members removed since they approved no longer count
+        let approvals = proposal
+            .approvals
+            .iter()
+            .filter(|approver| treasury.multisig_signers.contains(approver))
+            .count();
+        require!(
+            approvals >= treasury.multisig_threshold as usize,
+            StakeError::ThresholdNotReached
+        );
+
+        match proposal.action.clone() {
+            TreasuryAction::Withdraw { recipient, amount } => {
+                let recipient_account = ctx
+                    .accounts
+                    .recipient
+                    .as_ref()
+                    .ok_or(StakeError::InvalidRecipient)?;
+                require_keys_eq!(recipient_account.key(), recipient, StakeError::InvalidRecipient);
+                require!(amount > 0, StakeError::InvalidAmount);
+
+                let balance = treasury.to_account_info().lamports();
+                let rent_exemption = Rent::get()?.minimum_balance(treasury.to_account_info().data_len());
+                require!(
+                    balance.saturating_sub(rent_exemption) >= amount,
+                    StakeError::InsufficientTreasuryFunds
+                );
+
+                **treasury.to_account_info().try_borrow_mut_lamports()? -= amount;
+                **recipient_account.to_account_info().try_borrow_mut_lamports()? += amount;
+
+                emit!(TreasuryWithdrawn {
+                    recipient,
+                    amount,
+                    timestamp: Clock::get()?.unix_timestamp,
+                });
+            }
+            TreasuryAction::SetRates { points_per_sol_per_day, points_per_sol_payout } => {
+                update_rates(treasury, member, points_per_sol_per_day, points_per_sol_payout)?;
+            }
+            TreasuryAction::SetPaused { paused, reason } => {
+                update_paused(treasury, member, paused, if paused { reason } else { 0 })?;
+            }
+            TreasuryAction::SetMultisig { signers, threshold } => {
+                apply_multisig(treasury, signers, threshold)?;
+            }
+        }
+
+        proposal.executed = true;
+
+        emit!(ProposalExecuted {
+            proposal_id: proposal.id,
+            executor: member,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+/// Pauses or resumes conversions, recording `by` as the admin in the event
+fn update_paused(treasury: &mut Treasury, by: Pubkey, paused: bool, reason: u8) -> Result<()> {
+    treasury.paused = paused;
+    treasury.pause_reason = reason;
+
+    emit!(ConversionsPauseUpdated {
+        admin: by,
+        paused,
+        reason,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+/// Changes the emission and payout rates after accruing points at the old rate
+fn update_rates(
+    treasury: &mut Treasury,
+    by: Pubkey,
+    points_per_sol_per_day: u64,
+    points_per_sol_payout: u64,
+) -> Result<()> {
+    require!(points_per_sol_payout > 0, StakeError::InvalidRate);
+
+    let clock = Clock::get()?;
+    accrue_points(treasury, clock.unix_timestamp)?;
+    treasury.points_per_sol_per_day = points_per_sol_per_day;
+    treasury.points_per_sol_payout = points_per_sol_payout;
+
+    emit!(RatesUpdated {
+        admin: by,
+        points_per_sol_per_day,
+        points_per_sol_payout,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
 }
-/// Updates the user’s points based on staked amount and time elapsed
-fn update_points(pda_account: &mut StakeAccount, current_time: i64) -> Result<()> {
+/// Validates and stores a multisig signer set and threshold
+fn apply_multisig(treasury: &mut Treasury, signers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+    require!(
+        threshold > 0
+            && threshold as usize <= signers.len()
+            && signers.len() <= MAX_MULTISIG_SIGNERS,
+        StakeError::InvalidMultisig
+    );
+    for (i, signer) in signers.iter().enumerate() {
+        require!(!signers[..i].contains(signer), StakeError::InvalidMultisig);
+    }
+
+    treasury.multisig_signers = signers;
+    treasury.multisig_threshold = threshold;
+    Ok(())
+}
+/// Fails with `ConversionsPaused` while the admin has conversions paused
+fn ensure_conversions_enabled(treasury: &Treasury) -> Result<()> {
+    require!(!treasury.paused, StakeError::ConversionsPaused);
+    Ok(())
+}
+/// Brings the global points-per-lamport accumulator up to `current_time`
+/// at the current emission rate
+fn accrue_points(treasury: &mut Treasury, current_time: i64) -> Result<()> {
+    treasury.points_per_lamport = current_points_per_lamport(treasury, current_time)?;
+    treasury.last_accrual_time = current_time;
+
+    Ok(())
+}
+/// Value the points-per-lamport accumulator would have at `current_time`
+fn current_points_per_lamport(treasury: &Treasury, current_time: i64) -> Result<u128> {
     let time_elapsed = current_time
-        .checked_sub(pda_account.last_update_time)
+        .checked_sub(treasury.last_accrual_time)
         .ok_or(StakeError::InvalidTimestamp)? as u64;
 
-    if time_elapsed > 0 && pda_account.staked_amount > 0 {
-        let new_points = calculate_points_earned(pda_account.staked_amount, time_elapsed)?;
-        pda_account.total_points = pda_account
-            .total_points
-            .checked_add(new_points)
-            .ok_or(StakeError::Overflow)?;
-    }
+    (time_elapsed as u128)
+        .checked_mul(treasury.points_per_sol_per_day as u128)
+        .and_then(|accrued| accrued.checked_add(treasury.points_per_lamport))
+        .ok_or(StakeError::Overflow.into())
+}
+/// Updates the user’s points based on staked amount and the accumulator movement since their last update
+fn update_points(pda_account: &mut StakeAccount, treasury: &mut Treasury, current_time: i64) -> Result<()> {
+    accrue_points(treasury, current_time)?;
 
+    let new_points = calculate_points_earned(
+        pda_account.staked_amount,
+        treasury
+            .points_per_lamport
+            .checked_sub(pda_account.points_per_lamport_paid)
+            .ok_or(StakeError::Underflow)?,
+    )?;
+    pda_account.total_points = pda_account
+        .total_points
+        .checked_add(new_points)
+        .ok_or(StakeError::Overflow)?;
+
+    pda_account.points_per_lamport_paid = treasury.points_per_lamport;
     pda_account.last_update_time = current_time;
 
     Ok(())
 }
-/// Calculates how many points should be earned given staked amount and elapsed time
-fn calculate_points_earned(staked_amount: u64, time_elapsed_seconds: u64) -> Result<u64> {
+/// Calculates how many points `staked_amount` earned while the accumulator moved by `points_per_lamport`
+fn calculate_points_earned(staked_amount: u64, points_per_lamport: u128) -> Result<u64> {
     let points = (staked_amount as u128)
-        .checked_mul(time_elapsed_seconds as u128)
-        .ok_or(StakeError::Overflow)?
-        .checked_mul(POINTS_PER_SOL_PER_DAY as u128)
+        .checked_mul(points_per_lamport)
         .ok_or(StakeError::Overflow)?
-        .checked_div(LAMPORTS_PER_SOL as u128)
-        .ok_or(StakeError::DivisionByZero)?
-        .checked_div(SECONDS_PER_DAY as u128)
+        .checked_div(POINTS_SCALE)
         .ok_or(StakeError::DivisionByZero)?;
 
     if points > u64::MAX as u128 {
@@ -287,21 +614,6 @@ fn calculate_points_earned(staked_amount: u64, time_elapsed_seconds: u64) -> Res
 
     Ok(points as u64)
 }
-/// Admin-only: pauses conversion of points → SOL
-pub fn pause_conversions(ctx: Context<AdminOnly>) -> Result<()> {
-    let treasury = &mut ctx.accounts.treasury;
-    treasury.paused = true;
-    msg!("⏸️ Point conversions PAUSED by admin");
-    Ok(())
-}
-/// Admin-only: resumes conversion of points → SOL
-pub fn unpause_conversions(ctx: Context<AdminOnly>) -> Result<()> {
-    let treasury = &mut ctx.accounts.treasury;
-    treasury.paused = false;
-    msg!("▶️ Point conversions RESUMED by admin");
-    Ok(())
-}
-
 
 #[derive(Accounts)]
 pub struct InitializeTreasury<'info> {
@@ -311,7 +623,8 @@ pub struct InitializeTreasury<'info> {
     #[account(
         init,
         payer = admin,
-        space = 8 + 32 + 8 + 8 + 1 + 1,
+        space = 8 + 32 + 8 + 8 + 1 + 1 + 1 + 8 + 8 + 16 + 8 + 8 + 8 + 1 + 32
+            + 4 + 32 * MAX_MULTISIG_SIGNERS + 1 + 8,
         seeds = [b"treasury"],
         bump
     )]
@@ -343,7 +656,7 @@ pub struct CreatePdaAccount<'info> {
     #[account(
         init,
         payer = payer,
-        space = 8 + 32 + 8 + 8 + 8 + 1,
+        space = 8 + 32 + 8 + 8 + 8 + 1 + 16,
         seeds = [b"client", payer.key.as_ref()],
         bump
     )]
@@ -364,6 +677,13 @@ pub struct Stake<'info> {
         constraint = pda_account.owner == user.key() @ StakeError::Unauthorized
     )]
     pub pda_account: Account<'info, StakeAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
     
     pub system_program: Program<'info, System>,
 }
@@ -380,6 +700,13 @@ pub struct Unstake<'info> {
         constraint = pda_account.owner == user.key() @ StakeError::Unauthorized 
     )]
     pub pda_account: Account<'info, StakeAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
 }
 
 #[derive(Accounts)]
@@ -393,7 +720,14 @@ pub struct ClaimPoints<'info> {
         bump = pda_account.bump,
         constraint = pda_account.owner == user.key() @ StakeError::Unauthorized
     )]
-    pub pda_account: Account<'info, StakeAccount>
+    pub pda_account: Account<'info, StakeAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
 }
 
 #[derive(Accounts)]
@@ -428,6 +762,12 @@ pub struct GetPoints<'info> {
         constraint = pda_account.owner == user.key() @ StakeError::Unauthorized
     )]
     pub pda_account: Account<'info, StakeAccount>,
+
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
 }
 
 #[derive(Accounts)]
@@ -453,6 +793,89 @@ pub struct AdminOnly<'info> {
     pub treasury: Account<'info, Treasury>,
 }
 
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub new_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        constraint = treasury.pending_admin == Some(new_admin.key()) @ StakeError::Unauthorized
+    )]
+    pub treasury: Account<'info, Treasury>,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        constraint = treasury.multisig_signers.contains(&proposer.key()) @ StakeError::NotMultisigMember
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + Proposal::INIT_SPACE,
+        seeds = [b"proposal", treasury.proposal_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+    pub member: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        constraint = treasury.multisig_signers.contains(&member.key()) @ StakeError::NotMultisigMember
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        constraint = !proposal.executed @ StakeError::ProposalAlreadyExecuted
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    pub member: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump,
+        constraint = treasury.multisig_signers.contains(&member.key()) @ StakeError::NotMultisigMember
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        constraint = !proposal.executed @ StakeError::ProposalAlreadyExecuted
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: only receives lamports; must match a withdrawal's recipient
+    #[account(mut)]
+    pub recipient: Option<UncheckedAccount<'info>>,
+}
+
 #[account]
 pub struct StakeAccount {
     pub owner: Pubkey,
@@ -460,6 +883,7 @@ pub struct StakeAccount {
     pub total_points: u64,
     pub last_update_time: i64,
     pub bump: u8,
+    pub points_per_lamport_paid: u128, // Treasury::points_per_lamport at last update
 }
 
 #[account]
@@ -469,6 +893,177 @@ pub struct Treasury {
     pub total_paid_out: u64,
     pub bump: u8,
     pub paused: bool,
+    pub pause_reason: u8,
+    pub points_per_sol_per_day: u64,
+    pub points_per_sol_payout: u64,
+    pub points_per_lamport: u128, // points earned per lamport staked since launch, scaled by POINTS_SCALE
+    pub last_accrual_time: i64,
+    pub low_balance_warning: u64,  // lamports; conversions below this emit TreasuryLowBalance
+    pub low_balance_critical: u64, // lamports; below this the event is flagged critical
+    pub pending_admin: Option<Pubkey>, // proposed by the admin, takes over on accept_admin
+    pub multisig_signers: Vec<Pubkey>, // up to MAX_MULTISIG_SIGNERS members
+    pub multisig_threshold: u8,         // 0 while the admin alone governs the treasury
+    pub proposal_count: u64,            // id of the next proposal
+}
+
+/// Sensitive treasury operations, run by `execute_proposal` once enough
+/// multisig members approve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub enum TreasuryAction {
+    Withdraw { recipient: Pubkey, amount: u64 },
+    SetRates { points_per_sol_per_day: u64, points_per_sol_payout: u64 },
+    SetPaused { paused: bool, reason: u8 },
+    SetMultisig {
+        #[max_len(MAX_MULTISIG_SIGNERS)]
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    },
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub action: TreasuryAction,
+    #[max_len(MAX_MULTISIG_SIGNERS)]
+    pub approvals: Vec<Pubkey>,
+    pub executed: bool,
+    pub bump: u8,
+}
+
+/// Return data of `get_points`; `staked_amount` is in lamports
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PointsInfo {
+    pub points: u64,
+    pub staked_amount: u64,
+    pub last_update_time: i64,
+    pub seconds_since_update: u64,
+}
+
+/// Return data of `get_treasury_info`, all amounts in lamports
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TreasuryInfo {
+    pub balance: u64,
+    pub available_balance: u64,
+    pub total_funded: u64,
+    pub total_paid_out: u64,
+}
+
+#[event]
+pub struct TreasuryFunded {
+    pub admin: Pubkey,
+    pub amount: u64,
+    pub total_funded: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Staked {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub staked_amount: u64,
+    pub points_before: u64,
+    pub points_after: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Unstaked {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub staked_amount: u64,
+    pub points_before: u64,
+    pub points_after: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PointsClaimed {
+    pub user: Pubkey,
+    pub points_claimed: u64,
+    pub points_before: u64,
+    pub points_after: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PointsConverted {
+    pub user: Pubkey,
+    pub points_converted: u64,
+    pub lamports_paid: u64,
+    pub points_before: u64,
+    pub points_after: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryLowBalance {
+    pub balance: u64,
+    pub warning_threshold: u64,
+    pub critical_threshold: u64,
+    pub critical: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminProposed {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AdminTransferred {
+    pub previous_admin: Pubkey,
+    pub new_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub action: TreasuryAction,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalApproved {
+    pub proposal_id: u64,
+    pub member: Pubkey,
+    pub approvals: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub proposal_id: u64,
+    pub executor: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TreasuryWithdrawn {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConversionsPauseUpdated {
+    pub admin: Pubkey,
+    pub paused: bool,
+    pub reason: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RatesUpdated {
+    pub admin: Pubkey,
+    pub points_per_sol_per_day: u64,
+    pub points_per_sol_payout: u64,
+    pub timestamp: i64,
 }
 
 #[error_code]
@@ -497,6 +1092,63 @@ pub enum StakeError {
     InsufficientTreasuryFunds,
     #[msg("Point conversions are temporarily paused")]
     ConversionsPaused,
+    #[msg("Points per SOL payout must be greater than 0")]
+    InvalidRate,
+    #[msg("Critical threshold must not exceed the warning threshold")]
+    InvalidThresholds,
+    #[msg("Needs 1 <= threshold <= signers <= 5 distinct signers")]
+    InvalidMultisig,
+    #[msg("This action is governed by the multisig")]
+    MultisigRequired,
+    #[msg("Signer is not a multisig member")]
+    NotMultisigMember,
+    #[msg("Member has already approved this proposal")]
+    AlreadyApproved,
+    #[msg("Proposal has already been executed")]
+    ProposalAlreadyExecuted,
+    #[msg("Not enough approvals to execute the proposal")]
+    ThresholdNotReached,
+    #[msg("Recipient does not match the proposal")]
+    InvalidRecipient,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn treasury(paused: bool) -> Treasury {
+        Treasury {
+            admin: Pubkey::new_unique(),
+            total_funded: 0,
+            total_paid_out: 0,
+            bump: 255,
+            paused,
+            pause_reason: 0,
+            points_per_sol_per_day: DEFAULT_POINTS_PER_SOL_PER_DAY,
+            points_per_sol_payout: DEFAULT_POINTS_PER_SOL_PAYOUT,
+            points_per_lamport: 0,
+            last_accrual_time: 0,
+            low_balance_warning: DEFAULT_LOW_BALANCE_WARNING,
+            low_balance_critical: DEFAULT_LOW_BALANCE_CRITICAL,
+            pending_admin: None,
+            multisig_signers: Vec::new(),
+            multisig_threshold: 0,
+            proposal_count: 0,
+        }
+    }
+
+    #[test]
+    fn paused_treasury_rejects_conversions() {
+        assert_eq!(
+            ensure_conversions_enabled(&treasury(true)).unwrap_err(),
+            StakeError::ConversionsPaused.into()
+        );
+    }
+
+    #[test]
+    fn unpaused_treasury_allows_conversions() {
+        assert!(ensure_conversions_enabled(&treasury(false)).is_ok());
+    }
 }
 
 // anchor build
@@ -10,9 +10,20 @@ describe("staking-contract", () => {
   const program = anchor.workspace.StakingContract as Program<StakingContract>;
 
   let pda: PublicKey;
+  let treasury: PublicKey;
   let stakeTime = 0;
-  const POINTS_PER_SOL_PER_DAY = 1_000_000;
+  const POINTS_PER_SOL_PER_DAY = 100_000;
   const SECONDS_PER_DAY = 86_400;
+
+  // Decodes the Anchor events emitted by a confirmed transaction
+  const eventsOf = async (tx: string) => {
+    const txInfo = await provider.connection.getTransaction(tx, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    return [...parser.parseLogs(txInfo.meta.logMessages)];
+  };
   // 🔹 Before tests: derive the PDA for the test user
   before(async () => {
     const [pdaAddress, bump] = PublicKey.findProgramAddressSync(
@@ -20,6 +31,26 @@ describe("staking-contract", () => {
       program.programId
     );
     pda = pdaAddress;
+    [treasury] = PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury")],
+      program.programId
+    );
+  });
+  // ✅ Test 0: Create the treasury, which also holds the points emission rates
+  it("initialize treasury", async () => {
+    await program.methods
+      .initializeTreasury()
+      .accounts({
+        admin: provider.publicKey,
+        treasury,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const treasuryAccount = await program.account.treasury.fetch(treasury);
+    assert(treasuryAccount.admin.equals(provider.publicKey), "Admin should match");
+    assert(treasuryAccount.pointsPerSolPerDay.toNumber() === 100_000, "Default emission rate");
+    assert(treasuryAccount.pointsPerSolPayout.toString() === "10000000000", "Default payout rate");
   });
   // ✅ Test 1: Create the PDA account for staking
   it("create account", async () => {
@@ -54,9 +85,10 @@ describe("staking-contract", () => {
       .accounts({
         user: provider.publicKey,
         pdaAccount: pda,
+        treasury,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
-      .rpc();
+      .rpc({ commitment: "confirmed" });
 
     const stakeAccount = await program.account.stakeAccount.fetch(pda);
     assert(
@@ -64,9 +96,15 @@ describe("staking-contract", () => {
       `Staked amount should be ${stakeAmount.toString()}`
     );
 
+    const [staked] = await eventsOf(tx);
+    assert.equal(staked.name, "staked");
+    assert(staked.data.user.equals(provider.publicKey), "Event should name the staker");
+    assert(staked.data.amount.eq(stakeAmount), "Event should carry the staked amount");
+    assert(staked.data.stakedAmount.eq(stakeAmount), "Event should carry the new total");
+
     const pdaBalance = await provider.connection.getBalance(pda);
     const rentExemptAmount = await provider.connection.getMinimumBalanceForRentExemption(
-      8 + 32 + 8 + 8 + 8 + 1
+      8 + 32 + 8 + 8 + 8 + 1 + 16
     );
 
     assert(
@@ -81,17 +119,21 @@ describe("staking-contract", () => {
   it("wait and check points accumulation", async () => {
     await new Promise(resolve => setTimeout(resolve, 2000));
 
-    const tx = await program.methods
+    // get_points is read-only: simulate it and decode the returned PointsInfo
+    const info = await program.methods
       .getPoints()
       .accounts({
         user: provider.publicKey,
         pdaAccount: pda,
+        treasury,
       })
-      .rpc();
+      .view();
 
     const stakeAccount = await program.account.stakeAccount.fetch(pda);
-    console.log("Current points:", stakeAccount.totalPoints.toNumber());
-    console.log("Get points transaction signature:", tx);
+    assert(info.stakedAmount.eq(stakeAccount.stakedAmount), "Returned stake should match the account");
+    assert(info.lastUpdateTime.eq(stakeAccount.lastUpdateTime), "Returned last update should match");
+    assert(info.points.toNumber() > 0, "Points should be accruing on the stake");
+    console.log("Current points:", info.points.toNumber());
   });
   // ✅ Test 4: Unstake 1 SOL and verify balances + points
   it("unstake 1 SOL", async () => {
@@ -103,6 +145,7 @@ describe("staking-contract", () => {
       .accounts({
         user: provider.publicKey,
         pdaAccount: pda,
+        treasury,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
       .rpc();
@@ -145,15 +188,16 @@ describe("staking-contract", () => {
   it("wait more time for additional points", async () => {
     await new Promise(resolve => setTimeout(resolve, 3000));
 
-    const tx = await program.methods
+    const info = await program.methods
       .getPoints()
       .accounts({
         user: provider.publicKey,
         pdaAccount: pda,
+        treasury,
       })
-      .rpc();
+      .view();
 
-    console.log("Get points after additional wait transaction signature:", tx);
+    console.log("Points after additional wait:", info.points.toNumber());
   });
   // ✅ Test 6: Claim all accumulated points, should reset to 0
   it("claim points", async () => {
@@ -168,6 +212,7 @@ describe("staking-contract", () => {
         .accounts({
           user: provider.publicKey,
           pdaAccount: pda,
+          treasury,
         })
         .rpc();
 
@@ -194,6 +239,7 @@ describe("staking-contract", () => {
         .accounts({
           user: provider.publicKey,
           pdaAccount: pda,
+          treasury,
         })
         .rpc();
 
@@ -220,6 +266,7 @@ describe("staking-contract", () => {
         .accounts({
           user: provider.publicKey,
           pdaAccount: pda,
+          treasury,
         })
         .rpc();
 
@@ -241,4 +288,392 @@ describe("staking-contract", () => {
       "Points:", stakeAccount.totalPoints.toNumber(),
       "Owner:", stakeAccount.owner.toBase58());
   });
-});
\ No newline at end of file
+  // ✅ Test 10: Admin pauses conversions and convert_points_to_sol is rejected
+  it("rejects point conversions while paused", async () => {
+    const pauseReason = 2;
+    await program.methods
+      .pauseConversions(pauseReason)
+      .accounts({
+        admin: provider.publicKey,
+        treasury,
+      })
+      .rpc();
+
+    let treasuryAccount = await program.account.treasury.fetch(treasury);
+    assert(treasuryAccount.paused, "Treasury should be paused");
+    assert(treasuryAccount.pauseReason === pauseReason, "Pause reason should be stored");
+
+    try {
+      await program.methods
+        .convertPointsToSol(new anchor.BN(1))
+        .accounts({
+          user: provider.publicKey,
+          pdaAccount: pda,
+          treasury,
+        })
+        .rpc();
+      assert.fail("Conversion should fail while paused");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "ConversionsPaused");
+    }
+
+    await program.methods
+      .unpauseConversions()
+      .accounts({
+        admin: provider.publicKey,
+        treasury,
+      })
+      .rpc();
+
+    treasuryAccount = await program.account.treasury.fetch(treasury);
+    assert(!treasuryAccount.paused, "Treasury should be unpaused");
+    assert(treasuryAccount.pauseReason === 0, "Pause reason should be cleared");
+  });
+  // ✅ Test 11: Treasury totals come back as typed return data
+  it("returns treasury info", async () => {
+    const fundAmount = new anchor.BN(LAMPORTS_PER_SOL);
+    const tx = await program.methods
+      .fundTreasury(fundAmount)
+      .accounts({
+        admin: provider.publicKey,
+        treasury,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc({ commitment: "confirmed" });
+
+    const [funded] = await eventsOf(tx);
+    assert.equal(funded.name, "treasuryFunded");
+    assert(funded.data.amount.eq(fundAmount), "Event should carry the funded amount");
+
+    const info = await program.methods
+      .getTreasuryInfo()
+      .accounts({ treasury })
+      .view();
+
+    const balance = await provider.connection.getBalance(treasury);
+    assert(info.balance.toNumber() === balance, "Balance should match the treasury lamports");
+    assert(info.availableBalance.toNumber() < balance, "Rent reserve is not available");
+    assert(info.totalFunded.eq(fundAmount), "Total funded should be reported");
+    assert(info.totalPaidOut.toNumber() === 0, "Nothing has been paid out");
+  });
+  // ✅ Test 12: Only the admin can change rates, and new rates apply from now on
+  it("updates emission rates going forward only", async () => {
+    const stranger = anchor.web3.Keypair.generate();
+    try {
+      await program.methods
+        .setRates(new anchor.BN(1), new anchor.BN(1))
+        .accounts({
+          admin: stranger.publicKey,
+          treasury,
+        })
+        .signers([stranger])
+        .rpc();
+      assert.fail("A non-admin should not be able to change rates");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "Unauthorized");
+    }
+
+    const before = await program.account.treasury.fetch(treasury);
+    await program.methods
+      .setRates(new anchor.BN(0), before.pointsPerSolPayout)
+      .accounts({
+        admin: provider.publicKey,
+        treasury,
+      })
+      .rpc();
+
+    const after = await program.account.treasury.fetch(treasury);
+    assert(after.pointsPerSolPerDay.toNumber() === 0, "Emission rate should be updated");
+
+    // With emission at 0, a fresh stake earns nothing from here on
+    await program.methods
+      .stake(new anchor.BN(LAMPORTS_PER_SOL))
+      .accounts({
+        user: provider.publicKey,
+        pdaAccount: pda,
+        treasury,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await new Promise(resolve => setTimeout(resolve, 2000));
+    await program.methods
+      .unstake(new anchor.BN(LAMPORTS_PER_SOL))
+      .accounts({
+        user: provider.publicKey,
+        pdaAccount: pda,
+        treasury,
+      })
+      .rpc();
+
+    const stakeAccount = await program.account.stakeAccount.fetch(pda);
+    assert(stakeAccount.totalPoints.toNumber() === 0, "No points should accrue at a zero rate");
+  });
+  // ✅ Test 13: Conversions report the payout and flag a treasury below the admin thresholds
+  it("emits conversion and low balance events", async () => {
+    try {
+      await program.methods
+        .setLowBalanceThresholds(new anchor.BN(1), new anchor.BN(2))
+        .accounts({
+          admin: provider.publicKey,
+          treasury,
+        })
+        .rpc();
+      assert.fail("Critical above warning should be rejected");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "InvalidThresholds");
+    }
+
+    // Warn below 100 SOL and treat anything below 50 SOL as critical
+    await program.methods
+      .setLowBalanceThresholds(new anchor.BN(100 * LAMPORTS_PER_SOL), new anchor.BN(50 * LAMPORTS_PER_SOL))
+      .accounts({
+        admin: provider.publicKey,
+        treasury,
+      })
+      .rpc();
+
+    // Cheap enough payouts that a couple of seconds of points convert to lamports
+    await program.methods
+      .setRates(new anchor.BN(100_000), new anchor.BN(10))
+      .accounts({
+        admin: provider.publicKey,
+        treasury,
+      })
+      .rpc();
+    await program.methods
+      .stake(new anchor.BN(LAMPORTS_PER_SOL))
+      .accounts({
+        user: provider.publicKey,
+        pdaAccount: pda,
+        treasury,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    await new Promise(resolve => setTimeout(resolve, 2000));
+
+    const tx = await program.methods
+      .convertPointsToSol(new anchor.BN(1))
+      .accounts({
+        user: provider.publicKey,
+        pdaAccount: pda,
+        treasury,
+      })
+      .rpc({ commitment: "confirmed" });
+
+    const events = await eventsOf(tx);
+    const converted = events.find(event => event.name === "pointsConverted");
+    const lowBalance = events.find(event => event.name === "treasuryLowBalance");
+    assert(converted.data.pointsConverted.toNumber() === 1, "Event should carry the converted points");
+    assert(converted.data.lamportsPaid.toNumber() === LAMPORTS_PER_SOL / 10, "Event should carry the payout");
+    assert(
+      converted.data.pointsAfter.gte(converted.data.pointsBefore.subn(1)),
+      "Accrued points are credited before the conversion"
+    );
+    assert(lowBalance.data.critical, "A treasury under 50 SOL should be flagged critical");
+    assert(lowBalance.data.warningThreshold.toNumber() === 100 * LAMPORTS_PER_SOL);
+  });
+  // ✅ Test 14: Admin rights move only through propose + accept
+  it("hands the treasury over to a new admin in two steps", async () => {
+    const newAdmin = anchor.web3.Keypair.generate();
+    const stranger = anchor.web3.Keypair.generate();
+
+    try {
+      await program.methods
+        .proposeAdmin(stranger.publicKey)
+        .accounts({ admin: stranger.publicKey, treasury })
+        .signers([stranger])
+        .rpc();
+      assert.fail("Only the admin can propose a new admin");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "Unauthorized");
+    }
+
+    await program.methods
+      .proposeAdmin(newAdmin.publicKey)
+      .accounts({ admin: provider.publicKey, treasury })
+      .rpc();
+    let treasuryAccount = await program.account.treasury.fetch(treasury);
+    assert(treasuryAccount.pendingAdmin.equals(newAdmin.publicKey), "Pending admin should be stored");
+    assert(treasuryAccount.admin.equals(provider.publicKey), "Admin is unchanged until accepted");
+
+    try {
+      await program.methods
+        .acceptAdmin()
+        .accounts({ newAdmin: stranger.publicKey, treasury })
+        .signers([stranger])
+        .rpc();
+      assert.fail("Only the proposed admin can accept");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "Unauthorized");
+    }
+
+    await program.methods
+      .acceptAdmin()
+      .accounts({ newAdmin: newAdmin.publicKey, treasury })
+      .signers([newAdmin])
+      .rpc();
+    treasuryAccount = await program.account.treasury.fetch(treasury);
+    assert(treasuryAccount.admin.equals(newAdmin.publicKey), "New admin should be in charge");
+    assert(treasuryAccount.pendingAdmin === null, "Pending admin should be cleared");
+
+    // The previous admin has lost its rights
+    try {
+      await program.methods
+        .pauseConversions(1)
+        .accounts({ admin: provider.publicKey, treasury })
+        .rpc();
+      assert.fail("The previous admin should no longer be able to pause");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "Unauthorized");
+    }
+
+    // Hand it back so the provider stays admin for anything that follows
+    await program.methods
+      .proposeAdmin(provider.publicKey)
+      .accounts({ admin: newAdmin.publicKey, treasury })
+      .signers([newAdmin])
+      .rpc();
+    await program.methods
+      .acceptAdmin()
+      .accounts({ newAdmin: provider.publicKey, treasury })
+      .rpc();
+    treasuryAccount = await program.account.treasury.fetch(treasury);
+    assert(treasuryAccount.admin.equals(provider.publicKey), "Provider should be admin again");
+  });
+  // ✅ Test 15: Sensitive treasury actions need M-of-N multisig approval
+  it("runs withdrawals and pausing through a 2-of-3 multisig", async () => {
+    const memberB = anchor.web3.Keypair.generate();
+    const memberC = anchor.web3.Keypair.generate();
+    const stranger = anchor.web3.Keypair.generate();
+    const recipient = anchor.web3.Keypair.generate();
+    for (const member of [memberB, memberC]) {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(member.publicKey, LAMPORTS_PER_SOL)
+      );
+    }
+    const proposalPda = (id: number) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("proposal"), new anchor.BN(id).toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0];
+
+    await program.methods
+      .setMultisig([provider.publicKey, memberB.publicKey, memberC.publicKey], 2)
+      .accounts({ admin: provider.publicKey, treasury })
+      .rpc();
+
+    // The admin alone can no longer pause
+    try {
+      await program.methods
+        .pauseConversions(1)
+        .accounts({ admin: provider.publicKey, treasury })
+        .rpc();
+      assert.fail("Pausing should require the multisig");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "MultisigRequired");
+    }
+
+    const firstId = (await program.account.treasury.fetch(treasury)).proposalCount.toNumber();
+    try {
+      await program.methods
+        .createProposal({ setPaused: { paused: true, reason: 1 } })
+        .accounts({
+          proposer: stranger.publicKey,
+          treasury,
+          proposal: proposalPda(firstId),
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([stranger])
+        .rpc();
+      assert.fail("Non-members should not be able to propose");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "NotMultisigMember");
+    }
+
+    const withdrawAmount = new anchor.BN(LAMPORTS_PER_SOL / 5);
+    const withdrawal = proposalPda(firstId);
+    await program.methods
+      .createProposal({ withdraw: { recipient: recipient.publicKey, amount: withdrawAmount } })
+      .accounts({
+        proposer: provider.publicKey,
+        treasury,
+        proposal: withdrawal,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    // The proposer's own approval is 1 of the 2 needed
+    try {
+      await program.methods
+        .executeProposal()
+        .accounts({ member: provider.publicKey, treasury, proposal: withdrawal, recipient: recipient.publicKey })
+        .rpc();
+      assert.fail("Execution should wait for the threshold");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "ThresholdNotReached");
+    }
+
+    await program.methods
+      .approveProposal()
+      .accounts({ member: memberB.publicKey, treasury, proposal: withdrawal })
+      .signers([memberB])
+      .rpc();
+    try {
+      await program.methods
+        .approveProposal()
+        .accounts({ member: memberB.publicKey, treasury, proposal: withdrawal })
+        .signers([memberB])
+        .rpc();
+      assert.fail("A member should not approve twice");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "AlreadyApproved");
+    }
+
+    await program.methods
+      .executeProposal()
+      .accounts({ member: memberB.publicKey, treasury, proposal: withdrawal, recipient: recipient.publicKey })
+      .signers([memberB])
+      .rpc();
+    const recipientBalance = await provider.connection.getBalance(recipient.publicKey);
+    assert(recipientBalance === withdrawAmount.toNumber(), "Recipient should receive the withdrawal");
+    assert((await program.account.proposal.fetch(withdrawal)).executed, "Proposal should be marked executed");
+
+    try {
+      await program.methods
+        .executeProposal()
+        .accounts({ member: memberB.publicKey, treasury, proposal: withdrawal, recipient: recipient.publicKey })
+        .signers([memberB])
+        .rpc();
+      assert.fail("A proposal should only run once");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "ProposalAlreadyExecuted");
+    }
+
+    // Pausing goes through the same flow
+    const pause = proposalPda(firstId + 1);
+    await program.methods
+      .createProposal({ setPaused: { paused: true, reason: 3 } })
+      .accounts({
+        proposer: memberB.publicKey,
+        treasury,
+        proposal: pause,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([memberB])
+      .rpc();
+    await program.methods
+      .approveProposal()
+      .accounts({ member: memberC.publicKey, treasury, proposal: pause })
+      .signers([memberC])
+      .rpc();
+    await program.methods
+      .executeProposal()
+      .accounts({ member: memberC.publicKey, treasury, proposal: pause, recipient: null })
+      .signers([memberC])
+      .rpc();
+
+    const treasuryAccount = await program.account.treasury.fetch(treasury);
+    assert(treasuryAccount.paused, "Multisig should have paused conversions");
+    assert(treasuryAccount.pauseReason === 3, "Pause reason should come from the proposal");
+  });
+});
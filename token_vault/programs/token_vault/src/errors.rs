@@ -10,4 +10,32 @@ pub enum VaultError {
     UnauthorizedAccess,
     #[msg("Insufficient staked balance")]
     InsufficientStake,
+    #[msg("Reward vault does not belong to this vault")]
+    InvalidRewardVault,
+    #[msg("Reward rate must be greater than zero")]
+    InvalidRewardRate,
+    #[msg("Unknown reward stream, or no room for another one")]
+    InvalidRewardStream,
+    #[msg("Reward mint or token program does not match the reward stream")]
+    InvalidRewardMint,
+    #[msg("Unknown lock tier")]
+    InvalidLockTier,
+    #[msg("Stake is still locked")]
+    StakeLocked,
+    #[msg("This vault has an unbonding cooldown; use request_unstake")]
+    CooldownRequired,
+    #[msg("Too many unbonding requests pending")]
+    UnbondingQueueFull,
+    #[msg("Nothing has finished unbonding yet")]
+    NothingToWithdraw,
+    #[msg("Penalty must be at most 10000 basis points")]
+    InvalidPenalty,
+    #[msg("Only streams paying in the staking mint can be compounded")]
+    CompoundMintMismatch,
+    #[msg("This stake has not opted in to auto-compounding")]
+    AutoCompoundDisabled,
+    #[msg("Stake still holds shares, rewards or unbonding tokens")]
+    StakeNotEmpty,
+    #[msg("Stake has no lock boost that has ended")]
+    LockNotExpired,
 }
\ No newline at end of file
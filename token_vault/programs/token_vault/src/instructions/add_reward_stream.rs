@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::errors::*;
+use crate::state::*;
+
+/// Adds another reward token paid alongside the existing streams
+pub fn add_reward_stream(ctx: Context<AddRewardStream>, reward_rate_per_second: u64) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let now = Clock::get()?.unix_timestamp;
+    let index = vault.reward_stream_count as usize;
+
+    require!(index < MAX_REWARD_STREAMS, VaultError::InvalidRewardStream);
+    require!(
+        !vault
+            .active_streams()
+            .iter()
+            .any(|s| s.reward_mint == ctx.accounts.reward_mint.key()),
+        VaultError::InvalidRewardStream
+    );
+
+    vault.reward_streams[index] = RewardStream::new(
+        ctx.accounts.reward_mint.key(),
+        ctx.accounts.reward_vault.key(),
+        reward_rate_per_second,
+        now,
+    );
+    vault.reward_stream_count += 1;
+
+    msg!("Reward stream {} added for mint: {}", index, ctx.accounts.reward_mint.key());
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AddRewardStream<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", authority.key().as_ref()],
+        bump = vault.bump,
+        has_one = authority
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// CHECK: PDA signer for the reward vault
+    #[account(seeds = [b"authority", vault.key().as_ref()], bump = vault.authority_bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        token::mint = reward_mint,
+        token::authority = vault_authority,
+        token::token_program = reward_token_program,
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub reward_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
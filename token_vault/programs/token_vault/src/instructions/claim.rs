@@ -1,15 +1,29 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
 use crate::state::*;   
 use crate::errors::*;  
+use crate::instructions::shares::*;
+
+/// Accounts passed per paid stream, as remaining accounts:
+/// reward vault, reward mint, destination, token program
+const ACCOUNTS_PER_STREAM: usize = 4;
 
 #[derive(Accounts)]
 pub struct Claim<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"vault", vault.authority.as_ref()],
+        bump = vault.bump,
+    )]
     pub vault: Account<'info, Vault>,
 
-    #[account(mut, has_one = staker)]
+    #[account(
+        mut,
+        seeds = [b"user-stake", staker.key().as_ref(), vault.key().as_ref()],
+        bump = user_stake.bump,
+        has_one = staker
+    )]
     pub user_stake: Account<'info, UserStake>,
     
     pub staker: Signer<'info>,  
@@ -18,45 +32,95 @@ pub struct Claim<'info> {
     #[account(seeds = [b"authority", vault.key().as_ref()], bump = vault.authority_bump)]
     pub vault_authority: UncheckedAccount<'info>,
 
-    #[account(mut)]
-    pub reward_vault: Account<'info, TokenAccount>, // vault’s reward pool
+    // Early-exit penalties shared with this stake are minted here as shares
+    #[account(mut, address = vault.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
 
-    #[account(mut)]
-    pub destination: Account<'info, TokenAccount>, // user’s ATA for reward mint
+    #[account(mut, address = vault.share_escrow)]
+    pub share_escrow: InterfaceAccount<'info, TokenAccount>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
-pub fn claim(ctx: Context<Claim>) -> Result<()> {
+/// Pays out `stream` only, or every stream when `None`. Each paid stream's
+/// accounts are passed as remaining accounts, in stream order.
+pub fn claim<'info>(
+    ctx: Context<'_, '_, 'info, 'info, Claim<'info>>,
+    stream: Option<u8>,
+) -> Result<()> {
     let vault = &mut ctx.accounts.vault;
     let user = &mut ctx.accounts.user_stake;
     let now = Clock::get()?.unix_timestamp;
 
     vault.update_rewards(now);
+    let penalty_shares = user.harvest(vault);
+    // An expired lock stops boosting from here on
+    user.sync_weight(vault, now);
 
-    let earned = (user.amount as u128)
-        .saturating_mul(vault.acc_reward_per_share.saturating_sub(user.reward_debt))
-        / Vault::SCALING;
-    let payout = user.pending_rewards.saturating_add(earned as u64);
+    let streams = match stream {
+        Some(index) => {
+            require!(index < vault.reward_stream_count, VaultError::InvalidRewardStream);
+            index as usize..index as usize + 1
+        }
+        None => 0..vault.reward_stream_count as usize,
+    };
+    require!(
+        ctx.remaining_accounts.len() == streams.len() * ACCOUNTS_PER_STREAM,
+        VaultError::InvalidRewardStream
+    );
 
-    require!(payout > 0, VaultError::InsufficientFunds);
-    require!(ctx.accounts.reward_vault.amount >= payout, VaultError::InsufficientFunds);
     let vault_key = vault.key(); // keep it alive in a variable
     let seeds = &[b"authority", vault_key.as_ref(), &[vault.authority_bump]];
     let signer = &[&seeds[..]];
 
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.reward_vault.to_account_info(),
-        to: ctx.accounts.destination.to_account_info(),
-        authority: ctx.accounts.vault_authority.to_account_info(),
-    };
+    let mut total_payout = 0u64;
+    for (i, accounts) in streams.zip(ctx.remaining_accounts.chunks(ACCOUNTS_PER_STREAM)) {
+        let reward_stream = &mut vault.reward_streams[i];
+        let payout = user.pending_rewards[i];
+        if payout == 0 {
+            continue;
+        }
 
-    token::transfer(
-        CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, signer),
-        payout,
-    )?;
+        // Everything here is caller-supplied, so bind it to the stream
+        let reward_vault = InterfaceAccount::<TokenAccount>::try_from(&accounts[0])?;
+        let reward_mint = InterfaceAccount::<Mint>::try_from(&accounts[1])?;
+        let destination = InterfaceAccount::<TokenAccount>::try_from(&accounts[2])?;
+        let token_program = Interface::<TokenInterface>::try_from(&accounts[3])?;
+        require_keys_eq!(reward_vault.key(), reward_stream.reward_vault, VaultError::InvalidRewardVault);
+        require_keys_eq!(reward_mint.key(), reward_stream.reward_mint, VaultError::InvalidRewardMint);
+        require_keys_eq!(destination.mint, reward_stream.reward_mint, VaultError::InvalidRewardMint);
+        require_keys_eq!(*accounts[1].owner, token_program.key(), VaultError::InvalidRewardMint);
+
+        require!(reward_vault.amount >= payout, VaultError::InsufficientFunds);
 
-    user.pending_rewards = 0;
-    user.reward_debt = vault.acc_reward_per_share;
+        let cpi_accounts = TransferChecked {
+            from: reward_vault.to_account_info(),
+            mint: reward_mint.to_account_info(),
+            to: destination.to_account_info(),
+            authority: ctx.accounts.vault_authority.to_account_info(),
+        };
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer),
+            payout,
+            reward_mint.decimals,
+        )?;
+
+        reward_stream.total_rewards_distributed =
+            reward_stream.total_rewards_distributed.saturating_add(payout);
+        user.pending_rewards[i] = 0;
+        total_payout = total_payout.saturating_add(payout);
+    }
+
+    require!(total_payout > 0, VaultError::InsufficientFunds);
+
+    mint_shares(
+        &ctx.accounts.token_program,
+        &ctx.accounts.share_mint,
+        &ctx.accounts.share_escrow,
+        &ctx.accounts.vault_authority,
+        signer,
+        penalty_shares,
+    )?;
     Ok(())
 }
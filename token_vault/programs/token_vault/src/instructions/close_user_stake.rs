@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::*;
+use crate::state::*;
+
+/// Returns the rent of a stake with nothing left in it: no shares, no
+/// unclaimed rewards and no unbonding tokens
+pub fn close_user_stake(_ctx: Context<CloseUserStake>) -> Result<()> {
+    msg!("User stake closed");
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseUserStake<'info> {
+    #[account(
+        seeds = [b"vault", vault.authority.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"user-stake", authority.key().as_ref(), vault.key().as_ref()],
+        bump = user_stake.bump,
+        constraint = user_stake.is_empty() @ VaultError::StakeNotEmpty,
+        close = authority
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
@@ -0,0 +1,250 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::errors::*;
+use crate::instructions::shares::*;
+use crate::state::*;
+
+/// Restakes the staker's pending rewards from `stream`, which must pay in
+/// the staking mint, without a round-trip through their wallet
+pub fn compound(ctx: Context<Compound>, stream: u8) -> Result<()> {
+    let accounts = ctx.accounts;
+    compound_rewards(
+        &mut accounts.vault,
+        &mut accounts.user_stake,
+        &accounts.vault_authority,
+        &accounts.reward_vault,
+        &mut accounts.vault_token_account,
+        &accounts.mint,
+        &accounts.share_mint,
+        &accounts.share_escrow,
+        &accounts.token_program,
+        stream,
+    )
+}
+
+/// Keeper variant of `compound` for stakers who opted in with `set_auto_compound`
+pub fn crank_compound(ctx: Context<CrankCompound>, stream: u8) -> Result<()> {
+    let accounts = ctx.accounts;
+    compound_rewards(
+        &mut accounts.vault,
+        &mut accounts.user_stake,
+        &accounts.vault_authority,
+        &accounts.reward_vault,
+        &mut accounts.vault_token_account,
+        &accounts.mint,
+        &accounts.share_mint,
+        &accounts.share_escrow,
+        &accounts.token_program,
+        stream,
+    )
+}
+
+/// Lets a keeper compound this stake through `crank_compound`
+pub fn set_auto_compound(ctx: Context<SetAutoCompound>, enabled: bool) -> Result<()> {
+    ctx.accounts.user_stake.auto_compound = enabled;
+
+    msg!("Auto-compound {}", if enabled { "enabled" } else { "disabled" });
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn compound_rewards<'info>(
+    vault: &mut Account<'info, Vault>,
+    user: &mut Account<'info, UserStake>,
+    vault_authority: &UncheckedAccount<'info>,
+    reward_vault: &InterfaceAccount<'info, TokenAccount>,
+    vault_token_account: &mut InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    share_mint: &InterfaceAccount<'info, Mint>,
+    share_escrow: &InterfaceAccount<'info, TokenAccount>,
+    token_program: &Interface<'info, TokenInterface>,
+    stream: u8,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    vault.update_rewards(now);
+    let penalty_shares = user.harvest(vault);
+
+    let index = stream as usize;
+    let payout = user.pending_rewards[index];
+    require!(payout > 0, VaultError::InsufficientFunds);
+    require!(reward_vault.amount >= payout, VaultError::InsufficientFunds);
+
+    let vault_key = vault.key();
+    let seeds = &[b"authority", vault_key.as_ref(), &[vault.authority_bump]];
+    let signer = &[&seeds[..]];
+
+    // Move the rewards from reward_vault -> the stake pool
+    let balance_before = vault_token_account.amount;
+    let cpi_accounts = TransferChecked {
+        from: reward_vault.to_account_info(),
+        mint: mint.to_account_info(),
+        to: vault_token_account.to_account_info(),
+        authority: vault_authority.to_account_info(),
+    };
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer),
+        payout,
+        mint.decimals,
+    )?;
+    vault_token_account.reload()?;
+    let received = vault_token_account.amount.saturating_sub(balance_before);
+
+    let reward_stream = &mut vault.reward_streams[index];
+    reward_stream.total_rewards_distributed =
+        reward_stream.total_rewards_distributed.saturating_add(payout);
+    user.pending_rewards[index] = 0;
+
+    // Compounding is not a fresh stake: lock and exit-penalty timers stay put
+    let shares = vault.shares_for(received);
+    mint_shares(
+        token_program,
+        share_mint,
+        share_escrow,
+        vault_authority,
+        signer,
+        shares.saturating_add(penalty_shares),
+    )?;
+
+    user.shares = user.shares.saturating_add(shares);
+    vault.total_staked = vault.total_staked.saturating_add(received);
+    vault.total_shares = vault.total_shares.saturating_add(shares);
+    user.apply_lock(0, shares, now)?;
+    user.sync_weight(vault, now);
+
+    msg!("Compounded {} into stake", received);
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(stream: u8)]
+pub struct Compound<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.authority.as_ref()],
+        bump = vault.bump,
+        constraint = stream < vault.reward_stream_count @ VaultError::InvalidRewardStream
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"user-stake", staker.key().as_ref(), vault.key().as_ref()],
+        bump = user_stake.bump,
+        has_one = staker
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    pub staker: Signer<'info>,
+
+    /// CHECK: PDA authority for vault
+    #[account(seeds = [b"authority", vault.key().as_ref()], bump = vault.authority_bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        address = vault.reward_streams[stream as usize].reward_vault @ VaultError::InvalidRewardVault,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = vault.token_account,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    // Both the stake mint and the stream's reward mint
+    #[account(
+        address = vault.reward_streams[stream as usize].reward_mint @ VaultError::CompoundMintMismatch
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, address = vault.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, address = vault.share_escrow)]
+    pub share_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(stream: u8)]
+pub struct CrankCompound<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.authority.as_ref()],
+        bump = vault.bump,
+        constraint = stream < vault.reward_stream_count @ VaultError::InvalidRewardStream
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"user-stake", user_stake.staker.as_ref(), vault.key().as_ref()],
+        bump = user_stake.bump,
+        constraint = user_stake.auto_compound @ VaultError::AutoCompoundDisabled
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    pub keeper: Signer<'info>,
+
+    /// CHECK: PDA authority for vault
+    #[account(seeds = [b"authority", vault.key().as_ref()], bump = vault.authority_bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        address = vault.reward_streams[stream as usize].reward_vault @ VaultError::InvalidRewardVault,
+        token::mint = mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = vault.token_account,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    // Both the stake mint and the stream's reward mint
+    #[account(
+        address = vault.reward_streams[stream as usize].reward_mint @ VaultError::CompoundMintMismatch
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, address = vault.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, address = vault.share_escrow)]
+    pub share_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct SetAutoCompound<'info> {
+    #[account(
+        seeds = [b"vault", vault.authority.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"user-stake", staker.key().as_ref(), vault.key().as_ref()],
+        bump = user_stake.bump,
+        has_one = staker
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    pub staker: Signer<'info>,
+}
@@ -1,17 +1,18 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
 use crate::state::*;
 
 pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
-    let cpi_accounts = Transfer {
+    let cpi_accounts = TransferChecked {
         from: ctx.accounts.user_token_account.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
         to: ctx.accounts.vault_token_account.to_account_info(),
         authority: ctx.accounts.authority.to_account_info(),
     };
     let cpi_program = ctx.accounts.token_program.to_account_info();
     let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-    token::transfer(cpi_ctx, amount)?;
+    token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.mint.decimals)?;
     Ok(())
 }
 
@@ -26,11 +27,18 @@ pub struct Deposit<'info> {
     pub vault: Account<'info, Vault>,
 
     #[account(mut, token::authority = authority)]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    #[account(mut, address = vault.token_account)]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = vault.token_account,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
 
     pub authority: Signer<'info>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
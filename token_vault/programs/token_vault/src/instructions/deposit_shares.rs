@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::errors::*;
+use crate::instructions::shares::*;
+use crate::state::*;
+
+/// Moves `shares` from the signer's wallet into the share escrow, where they
+/// start earning rewards for the signer's stake
+pub fn deposit_shares(ctx: Context<DepositShares>, shares: u64) -> Result<()> {
+    require!(shares > 0, VaultError::InsufficientStake);
+    let now = Clock::get()?.unix_timestamp;
+
+    transfer_shares(
+        &ctx.accounts.token_program,
+        &ctx.accounts.share_mint,
+        &ctx.accounts.user_share_account,
+        &ctx.accounts.share_escrow,
+        ctx.accounts.authority.to_account_info(),
+        &[],
+        shares,
+    )?;
+
+    let vault_key = ctx.accounts.vault.key();
+    let seeds = &[b"authority", vault_key.as_ref(), &[ctx.accounts.vault.authority_bump]];
+    let signer = &[&seeds[..]];
+
+    let vault = &mut ctx.accounts.vault;
+    let user = &mut ctx.accounts.user_stake;
+
+    vault.update_rewards(now);
+
+    if user.staker == Pubkey::default() {
+        user.staker = ctx.accounts.authority.key();
+        user.bump = ctx.bumps.user_stake;
+    }
+
+    let penalty_shares = user.harvest(vault);
+    user.shares = user.shares.saturating_add(shares);
+    // Joining a running lock doesn't extend it, nor earn its full boost
+    user.apply_lock(0, shares, now)?;
+    user.sync_weight(vault, now);
+    // Shares arriving from elsewhere restart the early-exit clock, same as a
+    // fresh stake, so moving them around can't skip the penalty
+    user.last_update = now;
+
+    mint_shares(
+        &ctx.accounts.token_program,
+        &ctx.accounts.share_mint,
+        &ctx.accounts.share_escrow,
+        &ctx.accounts.vault_authority,
+        signer,
+        penalty_shares,
+    )?;
+
+    msg!("Deposited {} shares", shares);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DepositShares<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.authority.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + UserStake::INIT_SPACE,
+        seeds = [b"user-stake", authority.key().as_ref(), vault.key().as_ref()],
+        bump
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    #[account(mut, address = vault.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::authority = authority,
+        token::token_program = token_program
+    )]
+    pub user_share_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, address = vault.share_escrow)]
+    pub share_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: PDA authority of the share mint
+    #[account(
+        seeds = [b"authority", vault.key().as_ref()],
+        bump = vault.authority_bump
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
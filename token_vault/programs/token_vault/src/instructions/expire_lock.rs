@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::errors::*;
+use crate::instructions::shares::*;
+use crate::state::*;
+
+/// Drops the boost of a stake whose lock has ended. Anyone can call this, so
+/// a staker who stays idle past `lock_end` can't keep earning at the boosted
+/// weight until their next action.
+pub fn expire_lock(ctx: Context<ExpireLock>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+
+    let vault_key = ctx.accounts.vault.key();
+    let seeds = &[b"authority", vault_key.as_ref(), &[ctx.accounts.vault.authority_bump]];
+    let signer = &[&seeds[..]];
+
+    let vault = &mut ctx.accounts.vault;
+    let user = &mut ctx.accounts.user_stake;
+    require!(
+        now >= user.lock_end && user.lock_multiplier_bps > LOCK_TIERS[0].1,
+        VaultError::LockNotExpired
+    );
+
+    vault.update_rewards(now);
+    let penalty_shares = user.harvest(vault);
+    user.sync_weight(vault, now);
+
+    mint_shares(
+        &ctx.accounts.token_program,
+        &ctx.accounts.share_mint,
+        &ctx.accounts.share_escrow,
+        &ctx.accounts.vault_authority,
+        signer,
+        penalty_shares,
+    )?;
+
+    msg!("Lock boost expired");
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ExpireLock<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.authority.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"user-stake", user_stake.staker.as_ref(), vault.key().as_ref()],
+        bump = user_stake.bump,
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    pub keeper: Signer<'info>,
+
+    /// CHECK: PDA authority of the share mint
+    #[account(
+        seeds = [b"authority", vault.key().as_ref()],
+        bump = vault.authority_bump
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(mut, address = vault.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, address = vault.share_escrow)]
+    pub share_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
@@ -1,35 +1,85 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
 use crate::errors::VaultError;
+use crate::state::*;
 
 #[derive(Accounts)]
+#[instruction(stream: u8)]
 pub struct FundRewards<'info> {
-    #[account(mut)]
-    pub reward_vault: Account<'info, TokenAccount>, // vault ka reward pool
+    #[account(
+        mut,
+        seeds = [b"vault", authority.key().as_ref()],
+        bump = vault.bump,
+        has_one = authority,
+        constraint = stream < vault.reward_stream_count @ VaultError::InvalidRewardStream
+    )]
+    pub vault: Account<'info, Vault>,
 
-    #[account(mut)]
-    pub admin_reward_ata: Account<'info, TokenAccount>, // admin ka ATA
+    /// CHECK: PDA authority for vault
+    #[account(seeds = [b"authority", vault.key().as_ref()], bump = vault.authority_bump)]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        address = vault.reward_streams[stream as usize].reward_vault @ VaultError::InvalidRewardVault,
+        token::mint = reward_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>, // vault ka reward pool
+
+    #[account(mut, token::mint = reward_mint, token::token_program = token_program)]
+    pub admin_reward_ata: InterfaceAccount<'info, TokenAccount>, // admin ka ATA
+
+    #[account(address = vault.reward_streams[stream as usize].reward_mint)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
 
     pub authority: Signer<'info>, // admin jo fund karega
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
-pub fn fund_rewards(ctx: Context<FundRewards>, amount: u64) -> Result<()> {
+pub fn fund_rewards(ctx: Context<FundRewards>, stream: u8, amount: u64) -> Result<()> {
     require!(amount > 0, VaultError::InsufficientFunds);
+    // The emission period is derived from the rate, so it must be set first
+    require!(
+        ctx.accounts.vault.reward_streams[stream as usize].reward_rate_per_second > 0,
+        VaultError::InvalidRewardRate
+    );
 
-    token::transfer(
+    let now = Clock::get()?.unix_timestamp;
+    let balance_before = ctx.accounts.reward_vault.amount;
+
+    token_interface::transfer_checked(
         CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            TransferChecked {
                 from: ctx.accounts.admin_reward_ata.to_account_info(),
+                mint: ctx.accounts.reward_mint.to_account_info(),
                 to: ctx.accounts.reward_vault.to_account_info(),
                 authority: ctx.accounts.authority.to_account_info(),
             },
         ),
         amount,
+        ctx.accounts.reward_mint.decimals,
     )?;
 
+    // Only budget what actually arrived (transfer-fee mints withhold some)
+    ctx.accounts.reward_vault.reload()?;
+    let received = ctx.accounts.reward_vault.amount.saturating_sub(balance_before);
+
+    // Settle accrual up to now before the emission period moves
+    let vault = &mut ctx.accounts.vault;
+    vault.update_rewards(now);
+    let reward_stream = &mut vault.reward_streams[stream as usize];
+    reward_stream.extend_rewards(received, now);
+
+    msg!(
+        "Stream {} funded: {}, emitting until {}",
+        stream,
+        received,
+        reward_stream.reward_end_ts
+    );
     Ok(())
 }
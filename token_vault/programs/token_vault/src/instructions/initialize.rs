@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 use crate::state::*;
 
@@ -14,6 +14,15 @@ pub fn initialize_vault(
     // directly used in this function, but is necessary for the instruction.
     // The authority_bump parameter is what we actually need.
     let now = Clock::get()?.unix_timestamp;  
+
+    // The reward mint passed here becomes stream 0; add_reward_stream adds more
+    let mut reward_streams = [RewardStream::default(); MAX_REWARD_STREAMS];
+    reward_streams[0] = RewardStream::new(
+        ctx.accounts.reward_mint.key(),
+        ctx.accounts.reward_vault.key(),
+        reward_rate_per_second,
+        now,
+    );
     
     ctx.accounts.vault.set_inner(Vault {
         authority: ctx.accounts.payer.key(),
@@ -24,11 +33,18 @@ pub fn initialize_vault(
         unlock_timestamp: 0,
         total_staked: 0,
 
-        reward_mint: ctx.accounts.reward_mint.key(),
-        reward_vault: ctx.accounts.reward_vault.key(),
-        reward_rate_per_second,
-        acc_reward_per_share: 0,
-        last_reward_ts: now,
+        share_mint: ctx.accounts.share_mint.key(),
+        share_mint_bump: ctx.bumps.share_mint,
+        share_escrow: ctx.accounts.share_escrow.key(),
+        total_shares: 0,
+        total_weight: 0,
+        cooldown_seconds: 0,
+        min_stake_seconds: 0,
+        penalty_bps: 0,
+        acc_penalty_per_share: 0,
+
+        reward_stream_count: 1,
+        reward_streams,
     });
 
     Ok(())
@@ -58,22 +74,50 @@ pub struct InitializeVault<'info> {
         payer = payer,
         token::mint = mint,
         token::authority = vault_authority,
+        token::token_program = token_program,
+    )]
+    pub token_account: InterfaceAccount<'info, TokenAccount>,
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    // Receipt token for stakes, same decimals as the staking mint
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"shares", vault.key().as_ref()],
+        bump,
+        mint::decimals = mint.decimals,
+        mint::authority = vault_authority,
+        mint::token_program = token_program,
+    )]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    // Staked shares sit here; shares in wallets are transferable but don't earn
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"share-escrow", vault.key().as_ref()],
+        bump,
+        token::mint = share_mint,
+        token::authority = vault_authority,
+        token::token_program = token_program,
     )]
-    pub token_account: Account<'info, TokenAccount>,
-    pub mint: Account<'info, Mint>,
+    pub share_escrow: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         init,
         payer = payer,
         token::mint = reward_mint,
         token::authority = vault_authority,
+        token::token_program = reward_token_program,
     )]
-    pub reward_vault: Account<'info, TokenAccount>,
-    pub reward_mint: Account<'info, Mint>,
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+    pub reward_mint: InterfaceAccount<'info, Mint>,
 
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
+    // Stake and reward mints may live under different token programs
+    pub token_program: Interface<'info, TokenInterface>,
+    pub reward_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
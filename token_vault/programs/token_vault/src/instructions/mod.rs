@@ -20,3 +20,33 @@ pub use claim::*;
 pub mod fund_rewards;
 pub use fund_rewards::*;
 
+pub mod set_reward_rate;
+pub use set_reward_rate::*;
+
+pub mod add_reward_stream;
+pub use add_reward_stream::*;
+
+pub mod set_cooldown;
+pub mod request_unstake;
+pub mod withdraw_unbonded;
+pub use set_cooldown::*;
+pub use request_unstake::*;
+pub use withdraw_unbonded::*;
+
+pub mod set_exit_penalty;
+pub use set_exit_penalty::*;
+
+pub mod compound;
+pub use compound::*;
+
+pub mod shares;
+pub mod deposit_shares;
+pub mod withdraw_shares;
+pub use deposit_shares::*;
+pub use withdraw_shares::*;
+
+pub mod close_user_stake;
+pub use close_user_stake::*;
+
+pub mod expire_lock;
+pub use expire_lock::*;
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::errors::*;
+use crate::instructions::shares::*;
+use crate::state::*;
+
+/// First step of a cooldown unstake: `shares` are burned and stop earning
+/// rewards now, and their underlying tokens become withdrawable through
+/// `withdraw_unbonded` after the vault cooldown
+pub fn request_unstake(ctx: Context<RequestUnstake>, shares: u64) -> Result<()> {
+    let vault_key = ctx.accounts.vault.key();
+    let seeds = &[b"authority", vault_key.as_ref(), &[ctx.accounts.vault.authority_bump]];
+    let signer = &[&seeds[..]];
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(now >= ctx.accounts.user_stake.lock_end, VaultError::StakeLocked);
+
+    let vault = &mut ctx.accounts.vault;
+    let user = &mut ctx.accounts.user_stake;
+
+    vault.update_rewards(now);
+
+    let penalty_shares = user.harvest(vault);
+    require!(shares > 0 && user.shares >= shares, VaultError::InsufficientStake);
+    let payout = user.leave(vault, shares, now);
+
+    let release_ts = now.saturating_add(vault.cooldown_seconds);
+    user.push_unbonding(payout, release_ts)?;
+
+    mint_shares(
+        &ctx.accounts.token_program,
+        &ctx.accounts.share_mint,
+        &ctx.accounts.share_escrow,
+        &ctx.accounts.vault_authority,
+        signer,
+        penalty_shares,
+    )?;
+    burn_escrowed_shares(
+        &ctx.accounts.token_program,
+        &ctx.accounts.share_mint,
+        &ctx.accounts.share_escrow,
+        &ctx.accounts.vault_authority,
+        signer,
+        shares,
+    )?;
+
+    msg!("Unbonding {} until timestamp: {}", payout, release_ts);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RequestUnstake<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.authority.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"user-stake", authority.key().as_ref(), vault.key().as_ref()],
+        bump = user_stake.bump,
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    #[account(mut, address = vault.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, address = vault.share_escrow)]
+    pub share_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: PDA authority of the share mint
+    #[account(
+        seeds = [b"authority", vault.key().as_ref()],
+        bump = vault.authority_bump
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+/// Sets the unbonding delay for future `request_unstake` calls; 0 re-enables
+/// instant `unstake`. Entries already queued keep their release time.
+pub fn set_cooldown(ctx: Context<SetCooldown>, cooldown_seconds: i64) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    vault.cooldown_seconds = cooldown_seconds.max(0);
+
+    msg!("Unbonding cooldown set to {} seconds", vault.cooldown_seconds);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetCooldown<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", authority.key().as_ref()],
+        bump = vault.bump,
+        has_one = authority
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub authority: Signer<'info>,
+}
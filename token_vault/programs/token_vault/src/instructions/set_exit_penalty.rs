@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::*;
+use crate::state::*;
+
+/// Configures the early-exit penalty: leaving within `min_stake_seconds` of
+/// the last stake forfeits `penalty_bps` of the principal to remaining stakers
+pub fn set_exit_penalty(
+    ctx: Context<SetExitPenalty>,
+    min_stake_seconds: i64,
+    penalty_bps: u16,
+) -> Result<()> {
+    require!(penalty_bps as u64 <= BPS_DENOMINATOR, VaultError::InvalidPenalty);
+
+    let vault = &mut ctx.accounts.vault;
+    vault.min_stake_seconds = min_stake_seconds.max(0);
+    vault.penalty_bps = penalty_bps;
+
+    msg!(
+        "Exit penalty set to {} bps within {} seconds of staking",
+        penalty_bps,
+        vault.min_stake_seconds
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetExitPenalty<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", authority.key().as_ref()],
+        bump = vault.bump,
+        has_one = authority
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub authority: Signer<'info>,
+}
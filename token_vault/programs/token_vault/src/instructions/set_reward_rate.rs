@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::*;
+use crate::state::*;
+
+/// Changes a stream's reward rate now (`effective_ts` 0 or past) or schedules it for
+/// `effective_ts`. Accrual up to the switch always uses the old rate.
+pub fn set_reward_rate(
+    ctx: Context<SetRewardRate>,
+    stream: u8,
+    rate: u64,
+    effective_ts: i64,
+) -> Result<()> {
+    require!(rate > 0, VaultError::InvalidRewardRate);
+
+    let vault = &mut ctx.accounts.vault;
+    let now = Clock::get()?.unix_timestamp;
+
+    // Checkpoint acc_reward_per_share at the old rate (and apply any
+    // previously scheduled change that is already due)
+    vault.update_rewards(now);
+    let reward_stream = &mut vault.reward_streams[stream as usize];
+
+    if effective_ts <= now {
+        reward_stream.apply_reward_rate(rate, now);
+        reward_stream.scheduled_reward_rate = 0;
+        reward_stream.scheduled_rate_ts = 0;
+        msg!("Stream {} reward rate set to {} per second", stream, rate);
+    } else {
+        // A new schedule replaces any pending one
+        reward_stream.scheduled_reward_rate = rate;
+        reward_stream.scheduled_rate_ts = effective_ts;
+        msg!("Stream {} reward rate {} scheduled for timestamp: {}", stream, rate, effective_ts);
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(stream: u8)]
+pub struct SetRewardRate<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", authority.key().as_ref()],
+        bump = vault.bump,
+        has_one = authority,
+        constraint = stream < vault.reward_stream_count @ VaultError::InvalidRewardStream
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub authority: Signer<'info>,
+}
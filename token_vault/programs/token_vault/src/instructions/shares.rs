@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    self, Burn, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
+};
+
+/// Mints `amount` vault shares to `to`, signed by the vault authority PDA
+pub fn mint_shares<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    share_mint: &InterfaceAccount<'info, Mint>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    vault_authority: &UncheckedAccount<'info>,
+    signer: &[&[&[u8]]],
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let cpi_accounts = MintTo {
+        mint: share_mint.to_account_info(),
+        to: to.to_account_info(),
+        authority: vault_authority.to_account_info(),
+    };
+    token_interface::mint_to(
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer),
+        amount,
+    )
+}
+
+/// Burns `amount` shares out of the vault's share escrow
+pub fn burn_escrowed_shares<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    share_mint: &InterfaceAccount<'info, Mint>,
+    share_escrow: &InterfaceAccount<'info, TokenAccount>,
+    vault_authority: &UncheckedAccount<'info>,
+    signer: &[&[&[u8]]],
+    amount: u64,
+) -> Result<()> {
+    let cpi_accounts = Burn {
+        mint: share_mint.to_account_info(),
+        from: share_escrow.to_account_info(),
+        authority: vault_authority.to_account_info(),
+    };
+    token_interface::burn(
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer),
+        amount,
+    )
+}
+
+/// Moves `amount` shares between share accounts. `signer` is empty when
+/// `authority` signed the transaction itself.
+pub fn transfer_shares<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    share_mint: &InterfaceAccount<'info, Mint>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    authority: AccountInfo<'info>,
+    signer: &[&[&[u8]]],
+    amount: u64,
+) -> Result<()> {
+    let cpi_accounts = TransferChecked {
+        from: from.to_account_info(),
+        mint: share_mint.to_account_info(),
+        to: to.to_account_info(),
+        authority,
+    };
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer),
+        amount,
+        share_mint.decimals,
+    )
+}
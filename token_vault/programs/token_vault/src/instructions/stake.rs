@@ -1,42 +1,65 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
+use crate::errors::*;
+use crate::instructions::shares::*;
 use crate::state::*;
 
-fn harvest(user: &mut UserStake, vault: &Vault) {
-    if user.amount == 0 { 
-        user.reward_debt = vault.acc_reward_per_share; 
-        return; 
-    }
-    let pending = (user.amount as u128)
-        .saturating_mul(vault.acc_reward_per_share.saturating_sub(user.reward_debt))
-        / Vault::SCALING;
-    user.pending_rewards = user.pending_rewards.saturating_add(pending as u64);
-    user.reward_debt = vault.acc_reward_per_share;
-}
-
-pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+/// Stakes `amount` for vault shares kept in the share escrow, optionally
+/// locking the whole position for one of the `LOCK_TIERS` in exchange for a
+/// boosted reward weight
+pub fn stake(ctx: Context<Stake>, amount: u64, lock_tier: u8) -> Result<()> {
     let now = Clock::get()?.unix_timestamp;
 
-    {
+    // Transfer-fee mints withhold part of the transfer, so only credit the
+    // amount that actually reached the vault
+    let balance_before = ctx.accounts.vault_token_account.amount;
+    token_interface::transfer_checked(
+        ctx.accounts.into_transfer_to_vault_context(),
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+    ctx.accounts.vault_token_account.reload()?;
+    let received = ctx.accounts.vault_token_account.amount.saturating_sub(balance_before);
+
+    let shares = ctx.accounts.vault.shares_for(received);
+    require!(shares > 0, VaultError::InsufficientStake);
+
+    let vault_key = ctx.accounts.vault.key();
+    let seeds = &[b"authority", vault_key.as_ref(), &[ctx.accounts.vault.authority_bump]];
+    let signer = &[&seeds[..]];
+
+    let penalty_shares = {
         let vault = &mut ctx.accounts.vault;
         let user = &mut ctx.accounts.user_stake;
 
         vault.update_rewards(now);
 
-        if user.amount == 0 {
+        if user.staker == Pubkey::default() {
             user.staker = ctx.accounts.authority.key();
             user.bump = ctx.bumps.user_stake;
         }
 
-        harvest(user, vault);
+        let penalty_shares = user.harvest(vault);
 
-        user.amount = user.amount.saturating_add(amount);
-        vault.total_staked = vault.total_staked.saturating_add(amount);
+        user.shares = user.shares.saturating_add(shares);
+        vault.total_staked = vault.total_staked.saturating_add(received);
+        vault.total_shares = vault.total_shares.saturating_add(shares);
+        user.apply_lock(lock_tier, shares, now)?;
+        user.sync_weight(vault, now);
         user.last_update = now;
-        user.reward_debt = vault.acc_reward_per_share;
-    } 
-    token::transfer(ctx.accounts.into_transfer_to_vault_context(), amount)?;
+        penalty_shares
+    };
+
+    // New shares go straight into the escrow, where they earn for this staker
+    mint_shares(
+        &ctx.accounts.token_program,
+        &ctx.accounts.share_mint,
+        &ctx.accounts.share_escrow,
+        &ctx.accounts.vault_authority,
+        signer,
+        shares.saturating_add(penalty_shares),
+    )?;
 
     Ok(())
 }
@@ -61,24 +84,45 @@ pub struct Stake<'info> {
     pub user_stake: Account<'info, UserStake>,
 
     #[account(mut, token::authority = authority)]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = vault.token_account,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, address = vault.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
 
-    #[account(mut, address = vault.token_account)]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(mut, address = vault.share_escrow)]
+    pub share_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: PDA authority of the share mint
+    #[account(
+        seeds = [b"authority", vault.key().as_ref()],
+        bump = vault.authority_bump
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
 
     #[account(mut)]
     pub authority: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> Stake<'info> {
     pub fn into_transfer_to_vault_context(
         &self,
-    ) -> CpiContext<'_, '_, '_, 'info, token::Transfer<'info>> {
-        let cpi_accounts = token::Transfer {
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let cpi_accounts = TransferChecked {
             from: self.user_token_account.to_account_info(),
+            mint: self.mint.to_account_info(),
             to: self.vault_token_account.to_account_info(),
             authority: self.authority.to_account_info(),
         };
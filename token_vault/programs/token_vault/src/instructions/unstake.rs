@@ -1,36 +1,56 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 
 use crate::errors::*;
+use crate::instructions::shares::*;
 use crate::state::*;
 
-pub fn unstake(ctx: Context<Unstake>, amount: u64) -> Result<()> {
+/// Burns `shares` and pays out the underlying tokens right away
+pub fn unstake(ctx: Context<Unstake>, shares: u64) -> Result<()> {
     // clone values before mutable borrow to avoid conflicts
     let vault_key = ctx.accounts.vault.key();
     let authority_bump = ctx.accounts.vault.authority_bump;
 
+    // seeds for PDA authority
+    let seeds = &[b"authority", vault_key.as_ref(), &[authority_bump]];
+    let signer = &[&seeds[..]];
+
+    require!(ctx.accounts.vault.cooldown_seconds == 0, VaultError::CooldownRequired);
+
+    let now = Clock::get()?.unix_timestamp;
+    require!(now >= ctx.accounts.user_stake.lock_end, VaultError::StakeLocked);
+
     // mutable references
     let vault = &mut ctx.accounts.vault;
     let user = &mut ctx.accounts.user_stake;
 
-    require!(user.amount >= amount, VaultError::InsufficientStake);
-
-    let now = Clock::get()?.unix_timestamp;
     vault.update_rewards(now);
 
-    // harvest rewards before balance changes
-    let earned = (user.amount as u128)
-        .saturating_mul(vault.acc_reward_per_share.saturating_sub(user.reward_debt))
-        / Vault::SCALING;
-    user.pending_rewards = user.pending_rewards.saturating_add(earned as u64);
+    let penalty_shares = user.harvest(vault);
+    require!(shares > 0 && user.shares >= shares, VaultError::InsufficientStake);
+    let payout = user.leave(vault, shares, now);
 
-    // seeds for PDA authority
-    let seeds = &[b"authority", vault_key.as_ref(), &[authority_bump]];
-    let signer = &[&seeds[..]];
+    mint_shares(
+        &ctx.accounts.token_program,
+        &ctx.accounts.share_mint,
+        &ctx.accounts.share_escrow,
+        &ctx.accounts.vault_authority,
+        signer,
+        penalty_shares,
+    )?;
+    burn_escrowed_shares(
+        &ctx.accounts.token_program,
+        &ctx.accounts.share_mint,
+        &ctx.accounts.share_escrow,
+        &ctx.accounts.vault_authority,
+        signer,
+        shares,
+    )?;
 
     // build CPI transfer
-    let cpi_accounts = token::Transfer {
+    let cpi_accounts = TransferChecked {
         from: ctx.accounts.vault_token_account.to_account_info(),
+        mint: ctx.accounts.mint.to_account_info(),
         to: ctx.accounts.user_token_account.to_account_info(),
         authority: ctx.accounts.vault_authority.to_account_info(),
     };
@@ -40,12 +60,12 @@ pub fn unstake(ctx: Context<Unstake>, amount: u64) -> Result<()> {
         signer,
     );
 
-    token::transfer(cpi_ctx, amount)?;
+    token_interface::transfer_checked(cpi_ctx, payout, ctx.accounts.mint.decimals)?;
 
-    // update stake amounts
-    user.amount = user.amount.saturating_sub(amount);
-    vault.total_staked = vault.total_staked.saturating_sub(amount);
-    user.reward_debt = vault.acc_reward_per_share;
+    // Only a fully exited stake goes away; anything left keeps the account open
+    if ctx.accounts.user_stake.is_empty() {
+        ctx.accounts.user_stake.close(ctx.accounts.authority.to_account_info())?;
+    }
 
     Ok(())
 }
@@ -64,16 +84,28 @@ pub struct Unstake<'info> {
     #[account(
         mut,
         seeds = [b"user-stake", authority.key().as_ref(), vault.key().as_ref()],
-        bump,
-        close = authority
+        bump = user_stake.bump,
     )]
     pub user_stake: Account<'info, UserStake>,
 
     #[account(mut, token::authority = authority)]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = vault.token_account,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, address = vault.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
 
-    #[account(mut, address = vault.token_account)]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(mut, address = vault.share_escrow)]
+    pub share_escrow: InterfaceAccount<'info, TokenAccount>,
 
     /// CHECK: PDA signer for vault
     #[account(
@@ -84,5 +116,5 @@ pub struct Unstake<'info> {
 
     #[account(mut)]
     pub authority: Signer<'info>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
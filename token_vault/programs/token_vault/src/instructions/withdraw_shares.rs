@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::errors::*;
+use crate::instructions::shares::*;
+use crate::state::*;
+
+/// Moves `shares` out of the share escrow to any share account, e.g. to
+/// transfer or post them elsewhere. Withdrawn shares keep their claim on the
+/// vault's tokens but stop earning rewards until deposited again.
+pub fn withdraw_shares(ctx: Context<WithdrawShares>, shares: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(now >= ctx.accounts.user_stake.lock_end, VaultError::StakeLocked);
+
+    let vault_key = ctx.accounts.vault.key();
+    let seeds = &[b"authority", vault_key.as_ref(), &[ctx.accounts.vault.authority_bump]];
+    let signer = &[&seeds[..]];
+
+    let penalty_shares = {
+        let vault = &mut ctx.accounts.vault;
+        let user = &mut ctx.accounts.user_stake;
+
+        vault.update_rewards(now);
+        let penalty_shares = user.harvest(vault);
+
+        require!(shares > 0 && user.shares >= shares, VaultError::InsufficientStake);
+        user.shares -= shares;
+        user.sync_weight(vault, now);
+        penalty_shares
+    };
+
+    mint_shares(
+        &ctx.accounts.token_program,
+        &ctx.accounts.share_mint,
+        &ctx.accounts.share_escrow,
+        &ctx.accounts.vault_authority,
+        signer,
+        penalty_shares,
+    )?;
+
+    transfer_shares(
+        &ctx.accounts.token_program,
+        &ctx.accounts.share_mint,
+        &ctx.accounts.share_escrow,
+        &ctx.accounts.destination,
+        ctx.accounts.vault_authority.to_account_info(),
+        signer,
+        shares,
+    )?;
+
+    msg!("Withdrew {} shares", shares);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawShares<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault.authority.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"user-stake", authority.key().as_ref(), vault.key().as_ref()],
+        bump = user_stake.bump,
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    #[account(mut, address = vault.share_mint)]
+    pub share_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, address = vault.share_escrow)]
+    pub share_escrow: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = share_mint,
+        token::token_program = token_program
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: PDA authority of the share mint and escrow
+    #[account(
+        seeds = [b"authority", vault.key().as_ref()],
+        bump = vault.authority_bump
+    )]
+    pub vault_authority: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
@@ -40,18 +40,69 @@ pub mod token_vault {
         instructions::unlock::unlock_vault(ctx)
     }
 
-    pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
-        instructions::stake::stake(ctx, amount)
+    pub fn stake(ctx: Context<Stake>, amount: u64, lock_tier: u8) -> Result<()> {
+        instructions::stake::stake(ctx, amount, lock_tier)
     }
 
-    pub fn unstake(ctx: Context<Unstake>, amount: u64) -> Result<()> {
-        instructions::unstake::unstake(ctx, amount)
+    pub fn unstake(ctx: Context<Unstake>, shares: u64) -> Result<()> {
+        instructions::unstake::unstake(ctx, shares)
     }
-    pub fn claim(ctx: Context<Claim>) -> Result<()> {
-        instructions::claim::claim(ctx)
+    pub fn close_user_stake(ctx: Context<CloseUserStake>) -> Result<()> {
+        instructions::close_user_stake::close_user_stake(ctx)
     }
-    pub fn fund_rewards(ctx: Context<FundRewards>, amount: u64) -> Result<()> {
-        instructions::fund_rewards::fund_rewards(ctx, amount)
+    pub fn request_unstake(ctx: Context<RequestUnstake>, shares: u64) -> Result<()> {
+        instructions::request_unstake::request_unstake(ctx, shares)
+    }
+    pub fn withdraw_unbonded(ctx: Context<WithdrawUnbonded>) -> Result<()> {
+        instructions::withdraw_unbonded::withdraw_unbonded(ctx)
+    }
+    pub fn set_cooldown(ctx: Context<SetCooldown>, cooldown_seconds: i64) -> Result<()> {
+        instructions::set_cooldown::set_cooldown(ctx, cooldown_seconds)
+    }
+    pub fn set_exit_penalty(
+        ctx: Context<SetExitPenalty>,
+        min_stake_seconds: i64,
+        penalty_bps: u16,
+    ) -> Result<()> {
+        instructions::set_exit_penalty::set_exit_penalty(ctx, min_stake_seconds, penalty_bps)
+    }
+    pub fn compound(ctx: Context<Compound>, stream: u8) -> Result<()> {
+        instructions::compound::compound(ctx, stream)
+    }
+    pub fn crank_compound(ctx: Context<CrankCompound>, stream: u8) -> Result<()> {
+        instructions::compound::crank_compound(ctx, stream)
+    }
+    pub fn set_auto_compound(ctx: Context<SetAutoCompound>, enabled: bool) -> Result<()> {
+        instructions::compound::set_auto_compound(ctx, enabled)
+    }
+    pub fn deposit_shares(ctx: Context<DepositShares>, shares: u64) -> Result<()> {
+        instructions::deposit_shares::deposit_shares(ctx, shares)
+    }
+    pub fn withdraw_shares(ctx: Context<WithdrawShares>, shares: u64) -> Result<()> {
+        instructions::withdraw_shares::withdraw_shares(ctx, shares)
+    }
+    pub fn expire_lock(ctx: Context<ExpireLock>) -> Result<()> {
+        instructions::expire_lock::expire_lock(ctx)
+    }
+    pub fn claim<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Claim<'info>>,
+        stream: Option<u8>,
+    ) -> Result<()> {
+        instructions::claim::claim(ctx, stream)
+    }
+    pub fn fund_rewards(ctx: Context<FundRewards>, stream: u8, amount: u64) -> Result<()> {
+        instructions::fund_rewards::fund_rewards(ctx, stream, amount)
+    }
+    pub fn set_reward_rate(
+        ctx: Context<SetRewardRate>,
+        stream: u8,
+        rate: u64,
+        effective_ts: i64,
+    ) -> Result<()> {
+        instructions::set_reward_rate::set_reward_rate(ctx, stream, rate, effective_ts)
+    }
+    pub fn add_reward_stream(ctx: Context<AddRewardStream>, reward_rate_per_second: u64) -> Result<()> {
+        instructions::add_reward_stream::add_reward_stream(ctx, reward_rate_per_second)
     }
     
 }
@@ -1,5 +1,7 @@
 pub mod vault;
 pub mod user_stake;
+pub mod reward_stream;
 
 pub use vault::*;
-pub use user_stake::*;
\ No newline at end of file
+pub use user_stake::*;
+pub use reward_stream::*;
@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_REWARD_STREAMS: usize = 4;
+
+/// One reward token paid out to stakers, with its own rate and accumulator
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Default)]
+pub struct RewardStream {
+    pub reward_mint: Pubkey,
+    pub reward_vault: Pubkey,
+    pub reward_rate_per_second: u64,
+    pub acc_reward_per_share: u128,
+    pub last_reward_ts: i64,
+
+    pub total_rewards_funded: u64,
+    pub total_rewards_distributed: u64,
+    pub reward_end_ts: i64, // emissions stop here unless topped up
+
+    pub scheduled_reward_rate: u64,
+    pub scheduled_rate_ts: i64, // 0 when no rate change is scheduled
+}
+
+impl RewardStream {
+    pub const SCALING: u128 = 1_000_000_000_000;
+
+    /// A new stream: nothing is emitted until fund_rewards opens an emission period
+    pub fn new(reward_mint: Pubkey, reward_vault: Pubkey, reward_rate_per_second: u64, now: i64) -> Self {
+        Self {
+            reward_mint,
+            reward_vault,
+            reward_rate_per_second,
+            last_reward_ts: now,
+            reward_end_ts: now,
+            ..Default::default()
+        }
+    }
+
+    pub fn update_rewards(&mut self, now: i64, total_weight: u64) {
+        // A scheduled rate change splits the interval: accrue at the old rate
+        // up to the switch, then at the new one
+        if self.scheduled_rate_ts != 0 && now >= self.scheduled_rate_ts {
+            let switch_ts = self.scheduled_rate_ts;
+            self.accrue(switch_ts, total_weight);
+            self.apply_reward_rate(self.scheduled_reward_rate, switch_ts);
+            self.scheduled_reward_rate = 0;
+            self.scheduled_rate_ts = 0;
+        }
+        self.accrue(now, total_weight);
+    }
+
+    /// Switches to `rate` at `at`, stretching or shrinking `reward_end_ts` so
+    /// the unspent budget is still emitted in full. Accrue up to `at` first.
+    pub fn apply_reward_rate(&mut self, rate: u64, at: i64) {
+        // As in `extend_rewards`: a lapsed period must not accrue at the new
+        // rate for the unfunded gap before `at`
+        if at >= self.reward_end_ts {
+            self.last_reward_ts = at;
+        }
+
+        let leftover = (self.reward_end_ts.saturating_sub(at).max(0) as u128)
+            .saturating_mul(self.reward_rate_per_second as u128);
+        let duration = leftover / rate as u128;
+
+        self.reward_end_ts = at.saturating_add(duration.min(i64::MAX as u128) as i64);
+        self.reward_rate_per_second = rate;
+    }
+
+    fn accrue(&mut self, now: i64, total_weight: u64) {
+        // Nothing accrues past the end of the funded emission period
+        let applicable = now.min(self.reward_end_ts);
+
+        if total_weight == 0 {
+            // No one to pay: push the end out instead of burning the emissions
+            if self.last_reward_ts < self.reward_end_ts {
+                let idle = applicable.saturating_sub(self.last_reward_ts);
+                self.reward_end_ts = self.reward_end_ts.saturating_add(idle);
+            }
+            self.last_reward_ts = now;
+            return;
+        }
+        let dt = applicable.saturating_sub(self.last_reward_ts).max(0) as u128;
+        if dt == 0 { return; }
+
+        let rewards = dt.saturating_mul(self.reward_rate_per_second as u128);
+        let inc = rewards.saturating_mul(Self::SCALING) / (total_weight as u128);
+        self.acc_reward_per_share = self.acc_reward_per_share.saturating_add(inc);
+        self.last_reward_ts = applicable;
+    }
+
+    /// Adds `amount` to the emission budget and moves `reward_end_ts` so the
+    /// unspent rewards plus the new funds run out at the current rate
+    /// (Synthetix `notifyRewardAmount`). Call `update_rewards` first; the
+    /// rate must be non-zero.
+    pub fn extend_rewards(&mut self, amount: u64, now: i64) {
+        self.total_rewards_funded = self.total_rewards_funded.saturating_add(amount);
+
+        let leftover = (self.reward_end_ts.saturating_sub(now).max(0) as u128)
+            .saturating_mul(self.reward_rate_per_second as u128);
+        let available = leftover.saturating_add(amount as u128);
+        let duration = available / self.reward_rate_per_second as u128;
+
+        self.reward_end_ts = now.saturating_add(duration.min(i64::MAX as u128) as i64);
+        // A lapsed period must not accrue for the unfunded gap before `now`
+        self.last_reward_ts = now;
+    }
+}
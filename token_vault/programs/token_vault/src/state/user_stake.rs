@@ -1,13 +1,217 @@
 use anchor_lang::prelude::*;
 
+use super::reward_stream::*;
+use super::vault::*;
+use crate::errors::*;
+
+pub const SECONDS_PER_DAY: i64 = 86_400;
+pub const MAX_UNBONDING_ENTRIES: usize = 4;
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// (lock duration, reward weight multiplier in bps) for each lock tier passed to `stake`
+pub const LOCK_TIERS: [(i64, u64); 4] = [
+    (0, 10_000),                     // unlocked: 1x
+    (30 * SECONDS_PER_DAY, 12_500),  // 30 days: 1.25x
+    (90 * SECONDS_PER_DAY, 15_000),  // 90 days: 1.5x
+    (180 * SECONDS_PER_DAY, 20_000), // 180 days: 2x
+];
+
+/// Tokens taken out of the stake by `request_unstake`, paid by `withdraw_unbonded`
+/// once `release_ts` has passed. An `amount` of 0 marks a free slot.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Default)]
+pub struct UnbondingEntry {
+    pub amount: u64,
+    pub release_ts: i64,
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct UserStake {
     pub staker: Pubkey,
-    pub amount: u64,
+    pub shares: u64, // shares held for this staker in the vault's share escrow
     pub last_update: i64,
     pub bump: u8,
 
-    pub reward_debt: u128,
-    pub pending_rewards: u64,
-}
\ No newline at end of file
+    pub lock_end: i64,
+    pub lock_multiplier_bps: u64,
+    pub weight: u64, // shares boosted by the lock multiplier; what rewards accrue on
+
+    // Indexed like Vault::reward_streams
+    pub reward_debt: [u128; MAX_REWARD_STREAMS],
+    pub pending_rewards: [u64; MAX_REWARD_STREAMS],
+    pub penalty_debt: u128,
+
+    pub auto_compound: bool, // lets keepers call crank_compound for this stake
+
+    pub unbonding: [UnbondingEntry; MAX_UNBONDING_ENTRIES],
+}
+
+impl UserStake {
+    /// Moves rewards earned on every stream since the last checkpoint into
+    /// `pending_rewards`, and compounds early-exit penalties shared with this
+    /// stake into `shares`. Call after `Vault::update_rewards` and before the
+    /// weight changes, then `sync_weight`.
+    ///
+    /// Returns the shares added for penalties, which the caller mints into
+    /// the share escrow.
+    #[must_use]
+    pub fn harvest(&mut self, vault: &mut Vault) -> u64 {
+        for (i, stream) in vault.active_streams().iter().enumerate() {
+            let earned = (self.weight as u128)
+                .saturating_mul(stream.acc_reward_per_share.saturating_sub(self.reward_debt[i]))
+                / RewardStream::SCALING;
+            self.pending_rewards[i] = self.pending_rewards[i].saturating_add(earned as u64);
+            self.reward_debt[i] = stream.acc_reward_per_share;
+        }
+
+        // Penalty principal is already in the vault token account, outside
+        // total_staked until it is credited here
+        let penalties = ((self.weight as u128)
+            .saturating_mul(vault.acc_penalty_per_share.saturating_sub(self.penalty_debt))
+            / RewardStream::SCALING) as u64;
+        self.penalty_debt = vault.acc_penalty_per_share;
+
+        let shares = vault.shares_for(penalties);
+        self.shares = self.shares.saturating_add(shares);
+        vault.total_shares = vault.total_shares.saturating_add(shares);
+        vault.total_staked = vault.total_staked.saturating_add(penalties);
+        shares
+    }
+
+    /// Takes `shares` out of the stake and returns the underlying amount the
+    /// staker receives. Inside `min_stake_seconds` of the last stake or share
+    /// deposit, `penalty_bps` of it and the matching part of pending rewards
+    /// are forfeited to the other stakers. The penalty is kept out of the share
+    /// price, so neither this stake's remaining shares nor shares it moved to a
+    /// wallet earn any of it back. Harvest first.
+    pub fn leave(&mut self, vault: &mut Vault, shares: u64, now: i64) -> u64 {
+        let amount = vault.amount_for(shares);
+        let others_weight = vault.total_weight.saturating_sub(self.weight);
+        let early = now < self.last_update.saturating_add(vault.min_stake_seconds);
+
+        // With no one left to share with, there is nothing to penalize for
+        let (penalty, forfeit_bps) =
+            if early && vault.penalty_bps > 0 && others_weight > 0 {
+                let penalty = (amount as u128)
+                    .saturating_mul(vault.penalty_bps as u128)
+                    / BPS_DENOMINATOR as u128;
+                let forfeit_bps = (shares as u128)
+                    .saturating_mul(BPS_DENOMINATOR as u128)
+                    / self.shares.max(1) as u128;
+                (penalty as u64, forfeit_bps)
+            } else {
+                (0, 0)
+            };
+
+        self.shares = self.shares.saturating_sub(shares);
+        vault.total_shares = vault.total_shares.saturating_sub(shares);
+        vault.total_staked = vault.total_staked.saturating_sub(amount);
+        self.sync_weight(vault, now);
+
+        if penalty > 0 {
+            vault.acc_penalty_per_share = vault.acc_penalty_per_share.saturating_add(
+                (penalty as u128).saturating_mul(RewardStream::SCALING) / others_weight as u128,
+            );
+            self.penalty_debt = vault.acc_penalty_per_share;
+        }
+        if forfeit_bps > 0 {
+            for (i, stream) in vault.active_streams_mut().iter_mut().enumerate() {
+                let forfeited = (self.pending_rewards[i] as u128)
+                    .saturating_mul(forfeit_bps)
+                    / BPS_DENOMINATOR as u128;
+                self.pending_rewards[i] = self.pending_rewards[i].saturating_sub(forfeited as u64);
+                stream.acc_reward_per_share = stream.acc_reward_per_share.saturating_add(
+                    forfeited.saturating_mul(RewardStream::SCALING) / others_weight as u128,
+                );
+                // This stake's remainder must not earn back its own forfeit
+                self.reward_debt[i] = stream.acc_reward_per_share;
+            }
+        }
+
+        amount.saturating_sub(penalty)
+    }
+
+    /// Applies lock tier `tier` from `now` after `added_shares` joined the
+    /// stake. Locks only ever extend, and every share ends up locked until the
+    /// later of the current and new end. Shares only earn the multiplier of
+    /// the longest tier that fits in the time they are actually locked for, so
+    /// money added late to a long lock doesn't get that lock's full boost.
+    pub fn apply_lock(&mut self, tier: u8, added_shares: u64, now: i64) -> Result<()> {
+        let (duration, _) = *LOCK_TIERS
+            .get(tier as usize)
+            .ok_or(VaultError::InvalidLockTier)?;
+
+        if now >= self.lock_end {
+            self.lock_multiplier_bps = LOCK_TIERS[0].1;
+        }
+        self.lock_end = self.lock_end.max(now.saturating_add(duration));
+
+        let remaining = self.lock_end.saturating_sub(now);
+        let fit_bps = LOCK_TIERS
+            .iter()
+            .filter(|(tier_duration, _)| *tier_duration <= remaining)
+            .map(|&(_, tier_bps)| tier_bps)
+            .max()
+            .unwrap_or(LOCK_TIERS[0].1);
+
+        // Shares already here are locked at least as long as the new ones
+        let old_shares = self.shares.saturating_sub(added_shares) as u128;
+        let old_bps = self.lock_multiplier_bps.max(fit_bps) as u128;
+        let total_weight = old_shares
+            .saturating_mul(old_bps)
+            .saturating_add((added_shares as u128).saturating_mul(fit_bps as u128));
+        self.lock_multiplier_bps = match self.shares {
+            0 => fit_bps,
+            shares => (total_weight / shares as u128) as u64,
+        };
+        Ok(())
+    }
+
+    /// Recomputes `weight` from the recorded shares, dropping the boost once the
+    /// lock has ended, and moves the vault's total weight by the difference.
+    /// Harvest first: rewards up to now are owed at the old weight.
+    pub fn sync_weight(&mut self, vault: &mut Vault, now: i64) {
+        if now >= self.lock_end {
+            self.lock_multiplier_bps = LOCK_TIERS[0].1;
+        }
+        let weight = (self.shares as u128)
+            .saturating_mul(self.lock_multiplier_bps as u128)
+            / BPS_DENOMINATOR as u128;
+
+        vault.total_weight = vault
+            .total_weight
+            .saturating_sub(self.weight)
+            .saturating_add(weight as u64);
+        self.weight = weight as u64;
+    }
+
+    /// Queues `amount` for release at `release_ts` in the first free slot
+    pub fn push_unbonding(&mut self, amount: u64, release_ts: i64) -> Result<()> {
+        let slot = self
+            .unbonding
+            .iter_mut()
+            .find(|entry| entry.amount == 0)
+            .ok_or(VaultError::UnbondingQueueFull)?;
+        *slot = UnbondingEntry { amount, release_ts };
+        Ok(())
+    }
+
+    /// True once nothing is left to pay out, so the account can be closed
+    pub fn is_empty(&self) -> bool {
+        self.shares == 0
+            && self.pending_rewards.iter().all(|&pending| pending == 0)
+            && self.unbonding.iter().all(|entry| entry.amount == 0)
+    }
+
+    /// Clears every entry released by `now` and returns their total
+    pub fn take_matured_unbonding(&mut self, now: i64) -> u64 {
+        let mut total = 0u64;
+        for entry in self.unbonding.iter_mut() {
+            if entry.amount > 0 && now >= entry.release_ts {
+                total = total.saturating_add(entry.amount);
+                *entry = UnbondingEntry::default();
+            }
+        }
+        total
+    }
+}
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use super::reward_stream::*;
+
 #[account]
 #[derive(InitSpace)]
 pub struct Vault {
@@ -9,29 +11,55 @@ pub struct Vault {
     pub authority_bump: u8,
     pub is_locked: bool,
     pub unlock_timestamp: i64,
-    pub total_staked: u64,
+    pub total_staked: u64, // underlying tokens backing the shares
+
+    pub share_mint: Pubkey, // receipt token minted on stake, burned on unstake
+    pub share_mint_bump: u8,
+    pub share_escrow: Pubkey, // vault-owned share account; only shares held here earn rewards
+    pub total_shares: u64,
+    pub total_weight: u64, // sum of UserStake::weight; rewards are split by weight
+
+    pub cooldown_seconds: i64, // unbonding delay; 0 allows instant `unstake`
+
+    // Leaving within `min_stake_seconds` of the last stake costs `penalty_bps`
+    // of the principal, shared by the remaining stakers via acc_penalty_per_share
+    pub min_stake_seconds: i64,
+    pub penalty_bps: u16,
+    pub acc_penalty_per_share: u128,
 
-    pub reward_mint: Pubkey,
-    pub reward_vault: Pubkey,
-    pub reward_rate_per_second: u64,
-    pub acc_reward_per_share: u128, 
-    pub last_reward_ts: i64,
+    pub reward_stream_count: u8,
+    pub reward_streams: [RewardStream; MAX_REWARD_STREAMS],
 }
 
 impl Vault {
-    pub const SCALING: u128 = 1_000_000_000_000;
-
     pub fn update_rewards(&mut self, now: i64) {
-        if self.total_staked == 0 {
-            self.last_reward_ts = now;
-            return;
+        let total_weight = self.total_weight;
+        for stream in self.active_streams_mut() {
+            stream.update_rewards(now, total_weight);
         }
-        let dt = now.saturating_sub(self.last_reward_ts) as u128;
-        if dt == 0 { return; }
+    }
 
-        let rewards = dt.saturating_mul(self.reward_rate_per_second as u128);
-        let inc = rewards.saturating_mul(Self::SCALING) / (self.total_staked as u128);
-        self.acc_reward_per_share = self.acc_reward_per_share.saturating_add(inc);
-        self.last_reward_ts = now;
+    /// Shares minted for depositing `amount`; 1:1 for the first deposit
+    pub fn shares_for(&self, amount: u64) -> u64 {
+        if self.total_shares == 0 || self.total_staked == 0 {
+            return amount;
+        }
+        ((amount as u128).saturating_mul(self.total_shares as u128) / self.total_staked as u128) as u64
     }
-}
\ No newline at end of file
+
+    /// Underlying tokens redeemed by burning `shares`
+    pub fn amount_for(&self, shares: u64) -> u64 {
+        if self.total_shares == 0 {
+            return 0;
+        }
+        ((shares as u128).saturating_mul(self.total_staked as u128) / self.total_shares as u128) as u64
+    }
+
+    pub fn active_streams(&self) -> &[RewardStream] {
+        &self.reward_streams[..self.reward_stream_count as usize]
+    }
+
+    pub fn active_streams_mut(&mut self) -> &mut [RewardStream] {
+        &mut self.reward_streams[..self.reward_stream_count as usize]
+    }
+}
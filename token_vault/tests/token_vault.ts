@@ -1,16 +1,1295 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { TokenVault } from "../target/types/token_vault";
+import { Keypair, PublicKey, SystemProgram, Transaction, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+  ExtensionType,
+  createMint,
+  createAccount,
+  mintTo,
+  transfer,
+  getMintLen,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+} from "@solana/spl-token";
+import { assert } from "chai";
 
 describe("token_vault", () => {
   // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
 
   const program = anchor.workspace.tokenVault as Program<TokenVault>;
+  const payer = (provider.wallet as any).payer as Keypair;
 
-  it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
-    console.log("Your transaction signature", tx);
+  const rewardRate = new anchor.BN(1_000);
+  const stakeAmount = new anchor.BN(100_000_000); // 100 tokens
+
+  // Run the same flow against the legacy token program and Token-2022
+  const tokenPrograms: [string, PublicKey][] = [
+    ["spl-token", TOKEN_PROGRAM_ID],
+    ["token-2022", TOKEN_2022_PROGRAM_ID],
+  ];
+
+  for (const [label, tokenProgram] of tokenPrograms) {
+    describe(label, () => {
+      // Vaults are derived from their authority, so each run gets its own
+      const authority = Keypair.generate();
+      const tokenAccount = Keypair.generate();
+      const rewardVault = Keypair.generate();
+
+      let mint: PublicKey;
+      let rewardMint: PublicKey;
+      let vault: PublicKey;
+      let vaultAuthority: PublicKey;
+      let authorityBump: number;
+      let userStake: PublicKey;
+      let shareMint: PublicKey;
+      let shareEscrow: PublicKey;
+      let lockerStake: PublicKey; // locked staker that stays in the vault from its test on
+      let userTokenAccount: PublicKey;
+      let authorityRewardAccount: PublicKey;
+      let attackerRewardAccount: PublicKey;
+      let bonusMint: PublicKey;
+      let authorityBonusAccount: PublicKey;
+      const bonusVault = Keypair.generate();
+
+      before(async () => {
+        await provider.connection.confirmTransaction(
+          await provider.connection.requestAirdrop(authority.publicKey, 2 * LAMPORTS_PER_SOL)
+        );
+
+        mint = await createMint(
+          provider.connection, payer, provider.publicKey, null, 6, undefined, undefined, tokenProgram
+        );
+        rewardMint = await createMint(
+          provider.connection, payer, provider.publicKey, null, 6, undefined, undefined, tokenProgram
+        );
+
+        userTokenAccount = await createAccount(
+          provider.connection, payer, mint, authority.publicKey, undefined, undefined, tokenProgram
+        );
+        await mintTo(
+          provider.connection, payer, mint, userTokenAccount, provider.publicKey,
+          1_000_000_000, [], undefined, tokenProgram
+        );
+
+        // Funding source for the reward pool, plus a look-alike reward account
+        // owned by someone else for the substitution tests
+        authorityRewardAccount = await createAccount(
+          provider.connection, payer, rewardMint, authority.publicKey, undefined, undefined, tokenProgram
+        );
+        await mintTo(
+          provider.connection, payer, rewardMint, authorityRewardAccount, provider.publicKey,
+          1_000_000_000, [], undefined, tokenProgram
+        );
+        attackerRewardAccount = await createAccount(
+          provider.connection, payer, rewardMint, Keypair.generate().publicKey, undefined, undefined, tokenProgram
+        );
+        await mintTo(
+          provider.connection, payer, rewardMint, attackerRewardAccount, provider.publicKey,
+          1_000_000_000, [], undefined, tokenProgram
+        );
+
+        // Second reward token for the multi-stream tests
+        bonusMint = await createMint(
+          provider.connection, payer, provider.publicKey, null, 6, undefined, undefined, tokenProgram
+        );
+        authorityBonusAccount = await createAccount(
+          provider.connection, payer, bonusMint, authority.publicKey, undefined, undefined, tokenProgram
+        );
+        await mintTo(
+          provider.connection, payer, bonusMint, authorityBonusAccount, provider.publicKey,
+          1_000_000_000, [], undefined, tokenProgram
+        );
+
+        [vault] = PublicKey.findProgramAddressSync(
+          [Buffer.from("vault"), authority.publicKey.toBuffer()],
+          program.programId
+        );
+        [vaultAuthority, authorityBump] = PublicKey.findProgramAddressSync(
+          [Buffer.from("authority"), vault.toBuffer()],
+          program.programId
+        );
+        [userStake] = PublicKey.findProgramAddressSync(
+          [Buffer.from("user-stake"), authority.publicKey.toBuffer(), vault.toBuffer()],
+          program.programId
+        );
+        [shareMint] = PublicKey.findProgramAddressSync(
+          [Buffer.from("shares"), vault.toBuffer()],
+          program.programId
+        );
+        [shareEscrow] = PublicKey.findProgramAddressSync(
+          [Buffer.from("share-escrow"), vault.toBuffer()],
+          program.programId
+        );
+      });
+
+      it("Is initialized!", async () => {
+        await program.methods
+          .initializeVault(0, authorityBump, rewardRate)
+          .accounts({
+            vault,
+            vaultAuthority,
+            tokenAccount: tokenAccount.publicKey,
+            mint,
+            rewardVault: rewardVault.publicKey,
+            rewardMint,
+            shareMint,
+            shareEscrow,
+            payer: authority.publicKey,
+            tokenProgram,
+            rewardTokenProgram: tokenProgram,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority, tokenAccount, rewardVault])
+          .rpc();
+
+        const vaultAccount = await program.account.vault.fetch(vault);
+        assert.ok(vaultAccount.authority.equals(authority.publicKey));
+        assert.ok(vaultAccount.tokenAccount.equals(tokenAccount.publicKey));
+        assert.equal(vaultAccount.rewardStreamCount, 1);
+        assert.ok(vaultAccount.rewardStreams[0].rewardMint.equals(rewardMint));
+        assert.equal(vaultAccount.rewardStreams[0].rewardRatePerSecond.toString(), rewardRate.toString());
+      });
+
+      it("Stakes tokens into the vault", async () => {
+        await program.methods
+          .stake(stakeAmount, 0)
+          .accounts({
+            vault,
+            userStake,
+            userTokenAccount,
+            vaultTokenAccount: tokenAccount.publicKey,
+            mint,
+            shareMint,
+            shareEscrow,
+            vaultAuthority,
+            authority: authority.publicKey,
+            tokenProgram,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc();
+
+        const stakeAccount = await program.account.userStake.fetch(userStake);
+        const vaultAccount = await program.account.vault.fetch(vault);
+        const vaultBalance = await provider.connection.getTokenAccountBalance(tokenAccount.publicKey);
+
+        const shareBalance = await provider.connection.getTokenAccountBalance(shareEscrow);
+
+        // The first deposit mints shares 1:1
+        assert.equal(stakeAccount.shares.toString(), stakeAmount.toString());
+        assert.equal(stakeAccount.weight.toString(), stakeAmount.toString()); // unlocked: 1x
+        assert.equal(vaultAccount.totalStaked.toString(), stakeAmount.toString());
+        assert.equal(vaultAccount.totalShares.toString(), stakeAmount.toString());
+        assert.equal(vaultBalance.value.amount, stakeAmount.toString());
+        assert.equal(shareBalance.value.amount, stakeAmount.toString());
+      });
+
+      const fundRewardsAccounts = () => ({
+        vault,
+        vaultAuthority,
+        rewardVault: rewardVault.publicKey,
+        adminRewardAta: authorityRewardAccount,
+        rewardMint,
+        authority: authority.publicKey,
+        tokenProgram,
+      });
+
+      const claimAccounts = () => ({
+        vault,
+        userStake,
+        staker: authority.publicKey,
+        vaultAuthority,
+        shareMint,
+        shareEscrow,
+        tokenProgram,
+      });
+
+      // Claim takes each paid stream's accounts as remaining accounts
+      const streamAccounts = (streamVault: PublicKey, streamMint: PublicKey, destination: PublicKey) => [
+        { pubkey: streamVault, isWritable: true, isSigner: false },
+        { pubkey: streamMint, isWritable: false, isSigner: false },
+        { pubkey: destination, isWritable: true, isSigner: false },
+        { pubkey: tokenProgram, isWritable: false, isSigner: false },
+      ];
+
+      it("Funds the reward vault", async () => {
+        await program.methods
+          .fundRewards(0, new anchor.BN(500_000_000))
+          .accounts(fundRewardsAccounts())
+          .signers([authority])
+          .rpc();
+
+        const rewardBalance = await provider.connection.getTokenAccountBalance(rewardVault.publicKey);
+        assert.equal(rewardBalance.value.amount, "500000000");
+
+        // The budget sets how long emissions run at the configured rate
+        const stream = (await program.account.vault.fetch(vault)).rewardStreams[0];
+        assert.equal(stream.totalRewardsFunded.toString(), "500000000");
+        assert.isAbove(
+          stream.rewardEndTs.toNumber(),
+          stream.lastRewardTs.toNumber() + 500_000_000 / rewardRate.toNumber() - 5
+        );
+      });
+
+      it("Rejects funding a substituted reward vault", async () => {
+        try {
+          await program.methods
+            .fundRewards(0, new anchor.BN(1_000))
+            .accounts({ ...fundRewardsAccounts(), rewardVault: attackerRewardAccount })
+            .signers([authority])
+            .rpc();
+          assert.fail("funding a foreign reward vault should fail");
+        } catch (err) {
+          assert.equal(err.error.errorCode.code, "InvalidRewardVault");
+        }
+      });
+
+      it("Rejects funding from someone other than the vault authority", async () => {
+        const stranger = Keypair.generate();
+        try {
+          await program.methods
+            .fundRewards(0, new anchor.BN(1_000))
+            .accounts({ ...fundRewardsAccounts(), authority: stranger.publicKey })
+            .signers([stranger])
+            .rpc();
+          assert.fail("funding by a stranger should fail");
+        } catch (err) {
+          assert.equal(err.error.errorCode.code, "ConstraintSeeds");
+        }
+      });
+
+      it("Rejects claiming from a substituted reward vault", async () => {
+        try {
+          await program.methods
+            .claim(null)
+            .accounts(claimAccounts())
+            .remainingAccounts(streamAccounts(attackerRewardAccount, rewardMint, authorityRewardAccount))
+            .signers([authority])
+            .rpc();
+          assert.fail("claiming from a foreign reward vault should fail");
+        } catch (err) {
+          assert.equal(err.error.errorCode.code, "InvalidRewardVault");
+        }
+      });
+
+      it("Rejects claiming with a substituted reward mint", async () => {
+        try {
+          await program.methods
+            .claim(null)
+            .accounts(claimAccounts())
+            .remainingAccounts(streamAccounts(rewardVault.publicKey, mint, userTokenAccount))
+            .signers([authority])
+            .rpc();
+          assert.fail("claiming with the stake mint should fail");
+        } catch (err) {
+          assert.equal(err.error.errorCode.code, "InvalidRewardMint");
+        }
+      });
+
+      it("Rejects claiming into a destination of the wrong mint", async () => {
+        try {
+          await program.methods
+            .claim(null)
+            .accounts(claimAccounts())
+            .remainingAccounts(streamAccounts(rewardVault.publicKey, rewardMint, userTokenAccount))
+            .signers([authority])
+            .rpc();
+          assert.fail("claiming into a stake-mint account should fail");
+        } catch (err) {
+          assert.equal(err.error.errorCode.code, "InvalidRewardMint");
+        }
+      });
+
+      it("Claims accrued rewards from the vault's reward pool", async () => {
+        const balanceBefore = await provider.connection.getTokenAccountBalance(authorityRewardAccount);
+
+        await program.methods
+          .claim(null)
+          .accounts(claimAccounts())
+          .remainingAccounts(streamAccounts(rewardVault.publicKey, rewardMint, authorityRewardAccount))
+          .signers([authority])
+          .rpc();
+
+        const balanceAfter = await provider.connection.getTokenAccountBalance(authorityRewardAccount);
+        assert.ok(BigInt(balanceAfter.value.amount) > BigInt(balanceBefore.value.amount));
+
+        const stream = (await program.account.vault.fetch(vault)).rewardStreams[0];
+        assert.equal(
+          stream.totalRewardsDistributed.toString(),
+          (BigInt(balanceAfter.value.amount) - BigInt(balanceBefore.value.amount)).toString()
+        );
+      });
+
+      it("Changes the reward rate and rescales the emission period", async () => {
+        const before = (await program.account.vault.fetch(vault)).rewardStreams[0];
+        const newRate = rewardRate.muln(2);
+
+        await program.methods
+          .setRewardRate(0, newRate, new anchor.BN(0))
+          .accounts({ vault, authority: authority.publicKey })
+          .signers([authority])
+          .rpc();
+
+        // Doubling the rate roughly halves the time the remaining budget lasts
+        const after = (await program.account.vault.fetch(vault)).rewardStreams[0];
+        assert.equal(after.rewardRatePerSecond.toString(), newRate.toString());
+        const remainingBefore = before.rewardEndTs.sub(before.lastRewardTs).toNumber();
+        const remainingAfter = after.rewardEndTs.sub(after.lastRewardTs).toNumber();
+        assert.approximately(remainingAfter, remainingBefore / 2, 10);
+      });
+
+      it("Schedules a future reward rate change", async () => {
+        const effectiveTs = new anchor.BN(Math.floor(Date.now() / 1000) + 3600);
+
+        await program.methods
+          .setRewardRate(0, rewardRate, effectiveTs)
+          .accounts({ vault, authority: authority.publicKey })
+          .signers([authority])
+          .rpc();
+
+        // The current rate stays in force until the scheduled timestamp
+        const stream = (await program.account.vault.fetch(vault)).rewardStreams[0];
+        assert.equal(stream.rewardRatePerSecond.toString(), rewardRate.muln(2).toString());
+        assert.equal(stream.scheduledRewardRate.toString(), rewardRate.toString());
+        assert.equal(stream.scheduledRateTs.toString(), effectiveTs.toString());
+      });
+
+      it("Rejects reward rate changes from anyone but the vault authority", async () => {
+        const stranger = Keypair.generate();
+        try {
+          await program.methods
+            .setRewardRate(0, new anchor.BN(1), new anchor.BN(0))
+            .accounts({ vault, authority: stranger.publicKey })
+            .signers([stranger])
+            .rpc();
+          assert.fail("a stranger should not be able to change the rate");
+        } catch (err) {
+          assert.equal(err.error.errorCode.code, "ConstraintSeeds");
+        }
+      });
+
+      it("Adds and funds a second reward stream", async () => {
+        await program.methods
+          .addRewardStream(rewardRate)
+          .accounts({
+            vault,
+            vaultAuthority,
+            rewardVault: bonusVault.publicKey,
+            rewardMint: bonusMint,
+            authority: authority.publicKey,
+            rewardTokenProgram: tokenProgram,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority, bonusVault])
+          .rpc();
+
+        await program.methods
+          .fundRewards(1, new anchor.BN(500_000_000))
+          .accounts({
+            ...fundRewardsAccounts(),
+            rewardVault: bonusVault.publicKey,
+            adminRewardAta: authorityBonusAccount,
+            rewardMint: bonusMint,
+          })
+          .signers([authority])
+          .rpc();
+
+        const vaultAccount = await program.account.vault.fetch(vault);
+        assert.equal(vaultAccount.rewardStreamCount, 2);
+        assert.ok(vaultAccount.rewardStreams[1].rewardMint.equals(bonusMint));
+        assert.equal(vaultAccount.rewardStreams[1].totalRewardsFunded.toString(), "500000000");
+      });
+
+      it("Claims a single selected reward stream", async () => {
+        const rewardBefore = await provider.connection.getTokenAccountBalance(authorityRewardAccount);
+        const bonusBefore = await provider.connection.getTokenAccountBalance(authorityBonusAccount);
+
+        await program.methods
+          .claim(1)
+          .accounts(claimAccounts())
+          .remainingAccounts(streamAccounts(bonusVault.publicKey, bonusMint, authorityBonusAccount))
+          .signers([authority])
+          .rpc();
+
+        // Only the bonus stream pays out; stream 0 stays pending
+        const rewardAfter = await provider.connection.getTokenAccountBalance(authorityRewardAccount);
+        const bonusAfter = await provider.connection.getTokenAccountBalance(authorityBonusAccount);
+        assert.equal(rewardAfter.value.amount, rewardBefore.value.amount);
+        assert.ok(BigInt(bonusAfter.value.amount) > BigInt(bonusBefore.value.amount));
+      });
+
+      it("Claims every reward stream at once", async () => {
+        const rewardBefore = await provider.connection.getTokenAccountBalance(authorityRewardAccount);
+
+        await program.methods
+          .claim(null)
+          .accounts(claimAccounts())
+          .remainingAccounts([
+            ...streamAccounts(rewardVault.publicKey, rewardMint, authorityRewardAccount),
+            ...streamAccounts(bonusVault.publicKey, bonusMint, authorityBonusAccount),
+          ])
+          .signers([authority])
+          .rpc();
+
+        const rewardAfter = await provider.connection.getTokenAccountBalance(authorityRewardAccount);
+        assert.ok(BigInt(rewardAfter.value.amount) > BigInt(rewardBefore.value.amount));
+
+        const stakeAccount = await program.account.userStake.fetch(userStake);
+        assert.equal(stakeAccount.pendingRewards[0].toNumber(), 0);
+        assert.equal(stakeAccount.pendingRewards[1].toNumber(), 0);
+      });
+
+      const unstakeAccounts = () => ({
+        vault,
+        userStake,
+        userTokenAccount,
+        vaultTokenAccount: tokenAccount.publicKey,
+        mint,
+        shareMint,
+        shareEscrow,
+        vaultAuthority,
+        authority: authority.publicKey,
+        tokenProgram,
+      });
+
+      it("Keeps the stake and its rewards across a partial unstake", async () => {
+        const half = stakeAmount.divn(2);
+        await program.methods
+          .unstake(half)
+          .accounts(unstakeAccounts())
+          .signers([authority])
+          .rpc();
+
+        const stakeAccount = await program.account.userStake.fetch(userStake);
+        assert.equal(stakeAccount.shares.toString(), stakeAmount.sub(half).toString());
+
+        // Rewards earned before and after the partial unstake are still claimable
+        await new Promise((resolve) => setTimeout(resolve, 1500));
+        const rewardBefore = await provider.connection.getTokenAccountBalance(authorityRewardAccount);
+        await program.methods
+          .claim(null)
+          .accounts(claimAccounts())
+          .remainingAccounts([
+            ...streamAccounts(rewardVault.publicKey, rewardMint, authorityRewardAccount),
+            ...streamAccounts(bonusVault.publicKey, bonusMint, authorityBonusAccount),
+          ])
+          .signers([authority])
+          .rpc();
+        const rewardAfter = await provider.connection.getTokenAccountBalance(authorityRewardAccount);
+        assert.ok(BigInt(rewardAfter.value.amount) > BigInt(rewardBefore.value.amount));
+      });
+
+      it("Unstakes tokens back to the user", async () => {
+        const remaining = (await program.account.userStake.fetch(userStake)).shares;
+        await new Promise((resolve) => setTimeout(resolve, 1500));
+        const balanceBefore = await provider.connection.getTokenAccountBalance(userTokenAccount);
+
+        await program.methods
+          .unstake(remaining)
+          .accounts(unstakeAccounts())
+          .signers([authority])
+          .rpc();
+
+        const balanceAfter = await provider.connection.getTokenAccountBalance(userTokenAccount);
+        const vaultAccount = await program.account.vault.fetch(vault);
+
+        assert.equal(
+          balanceAfter.value.amount,
+          (BigInt(balanceBefore.value.amount) + BigInt(remaining.toString())).toString()
+        );
+        assert.equal(vaultAccount.totalStaked.toNumber(), 0);
+        assert.equal(vaultAccount.totalShares.toNumber(), 0);
+
+        const shareBalance = await provider.connection.getTokenAccountBalance(shareEscrow);
+        assert.equal(shareBalance.value.amount, "0");
+
+        // Rewards accrued since the last claim keep the account open
+        const stakeAccount = await program.account.userStake.fetch(userStake);
+        assert.ok(stakeAccount.pendingRewards[0].gtn(0));
+      });
+
+      it("Closes an emptied stake only once its rewards are claimed", async () => {
+        const closeAccounts = { vault, userStake, authority: authority.publicKey };
+        try {
+          await program.methods.closeUserStake().accounts(closeAccounts).signers([authority]).rpc();
+          assert.fail("closing with unclaimed rewards should fail");
+        } catch (err) {
+          assert.equal(err.error.errorCode.code, "StakeNotEmpty");
+        }
+
+        await program.methods
+          .claim(null)
+          .accounts(claimAccounts())
+          .remainingAccounts([
+            ...streamAccounts(rewardVault.publicKey, rewardMint, authorityRewardAccount),
+            ...streamAccounts(bonusVault.publicKey, bonusMint, authorityBonusAccount),
+          ])
+          .signers([authority])
+          .rpc();
+        await program.methods.closeUserStake().accounts(closeAccounts).signers([authority]).rpc();
+
+        assert.isNull(await provider.connection.getAccountInfo(userStake));
+      });
+
+      it("Locks a stake for a boosted weight and rejects early unstake", async () => {
+        const locker = Keypair.generate();
+        await provider.connection.confirmTransaction(
+          await provider.connection.requestAirdrop(locker.publicKey, LAMPORTS_PER_SOL)
+        );
+        const lockerTokenAccount = await createAccount(
+          provider.connection, payer, mint, locker.publicKey, undefined, undefined, tokenProgram
+        );
+        await mintTo(
+          provider.connection, payer, mint, lockerTokenAccount, provider.publicKey,
+          stakeAmount.toNumber(), [], undefined, tokenProgram
+        );
+        [lockerStake] = PublicKey.findProgramAddressSync(
+          [Buffer.from("user-stake"), locker.publicKey.toBuffer(), vault.toBuffer()],
+          program.programId
+        );
+
+        // Tier 2 locks for 90 days at a 1.5x reward weight
+        await program.methods
+          .stake(stakeAmount, 2)
+          .accounts({
+            vault,
+            userStake: lockerStake,
+            userTokenAccount: lockerTokenAccount,
+            vaultTokenAccount: tokenAccount.publicKey,
+            mint,
+            shareMint,
+            shareEscrow,
+            vaultAuthority,
+            authority: locker.publicKey,
+            tokenProgram,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([locker])
+          .rpc();
+
+        const stakeAccount = await program.account.userStake.fetch(lockerStake);
+        const vaultAccount = await program.account.vault.fetch(vault);
+        assert.equal(stakeAccount.weight.toString(), stakeAmount.muln(3).divn(2).toString());
+        assert.equal(vaultAccount.totalWeight.toString(), stakeAccount.weight.toString());
+        assert.isAbove(stakeAccount.lockEnd.toNumber(), Date.now() / 1000 + 89 * 86_400);
+
+        try {
+          await program.methods
+            .unstake(stakeAmount)
+            .accounts({
+              vault,
+              userStake: lockerStake,
+              userTokenAccount: lockerTokenAccount,
+              vaultTokenAccount: tokenAccount.publicKey,
+              mint,
+              shareMint,
+              shareEscrow,
+              vaultAuthority,
+              authority: locker.publicKey,
+              tokenProgram,
+            })
+            .signers([locker])
+            .rpc();
+          assert.fail("unstaking before lock_end should fail");
+        } catch (err) {
+          assert.equal(err.error.errorCode.code, "StakeLocked");
+        }
+
+        // Money added later with no tier of its own joins the lock, but with
+        // just under 90 days left it only earns the 30-day boost (1.25x)
+        await mintTo(
+          provider.connection, payer, mint, lockerTokenAccount, provider.publicKey,
+          stakeAmount.toNumber(), [], undefined, tokenProgram
+        );
+        await program.methods
+          .stake(stakeAmount, 0)
+          .accounts({
+            vault,
+            userStake: lockerStake,
+            userTokenAccount: lockerTokenAccount,
+            vaultTokenAccount: tokenAccount.publicKey,
+            mint,
+            shareMint,
+            shareEscrow,
+            vaultAuthority,
+            authority: locker.publicKey,
+            tokenProgram,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([locker])
+          .rpc();
+
+        const topped = await program.account.userStake.fetch(lockerStake);
+        assert.equal(topped.lockEnd.toString(), stakeAccount.lockEnd.toString());
+        assert.equal(topped.lockMultiplierBps.toNumber(), 13_750);
+        assert.equal(
+          topped.weight.toString(),
+          stakeAmount.muln(3).divn(2).add(stakeAmount.muln(5).divn(4)).toString()
+        );
+      });
+
+      it("Lets anyone drop a lock boost, but only once the lock has ended", async () => {
+        const keeper = Keypair.generate();
+        const expireAccounts = {
+          vault,
+          vaultAuthority,
+          shareMint,
+          shareEscrow,
+          keeper: keeper.publicKey,
+          tokenProgram,
+        };
+
+        // The locker is still inside its 90 days
+        try {
+          await program.methods
+            .expireLock()
+            .accounts({ ...expireAccounts, userStake: lockerStake })
+            .signers([keeper])
+            .rpc();
+          assert.fail("expiring a live lock should fail");
+        } catch (err) {
+          assert.equal(err.error.errorCode.code, "LockNotExpired");
+        }
+
+        const stakeAccount = await program.account.userStake.fetch(lockerStake);
+        assert.equal(stakeAccount.lockMultiplierBps.toNumber(), 13_750);
+      });
+
+      it("Unbonds through request_unstake and withdraw_unbonded after the cooldown", async () => {
+        const unstakeAccounts = {
+          vault,
+          userStake,
+          userTokenAccount,
+          vaultTokenAccount: tokenAccount.publicKey,
+          mint,
+          shareMint,
+          shareEscrow,
+          vaultAuthority,
+          authority: authority.publicKey,
+          tokenProgram,
+        };
+
+        await program.methods
+          .setCooldown(new anchor.BN(2))
+          .accounts({ vault, authority: authority.publicKey })
+          .signers([authority])
+          .rpc();
+        await program.methods
+          .stake(stakeAmount, 0)
+          .accounts({
+            vault,
+            userStake,
+            userTokenAccount,
+            vaultTokenAccount: tokenAccount.publicKey,
+            mint,
+            shareMint,
+            shareEscrow,
+            vaultAuthority,
+            authority: authority.publicKey,
+            tokenProgram,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc();
+
+        // Instant unstake is off while a cooldown is configured
+        try {
+          await program.methods.unstake(stakeAmount).accounts(unstakeAccounts).signers([authority]).rpc();
+          assert.fail("instant unstake should be rejected");
+        } catch (err) {
+          assert.equal(err.error.errorCode.code, "CooldownRequired");
+        }
+
+        const stakedBefore = (await program.account.vault.fetch(vault)).totalStaked;
+        await program.methods
+          .requestUnstake(stakeAmount)
+          .accounts({
+            vault,
+            userStake,
+            shareMint,
+            shareEscrow,
+            vaultAuthority,
+            authority: authority.publicKey,
+            tokenProgram,
+          })
+          .signers([authority])
+          .rpc();
+
+        // The amount stops counting towards the stake (and rewards) right away
+        const stakeAccount = await program.account.userStake.fetch(userStake);
+        const vaultAccount = await program.account.vault.fetch(vault);
+        assert.equal(stakeAccount.shares.toNumber(), 0);
+        assert.equal(stakeAccount.unbonding[0].amount.toString(), stakeAmount.toString());
+        assert.equal(vaultAccount.totalStaked.toString(), stakedBefore.sub(stakeAmount).toString());
+
+        try {
+          await program.methods.withdrawUnbonded().accounts(unstakeAccounts).signers([authority]).rpc();
+          assert.fail("withdrawing before the cooldown should fail");
+        } catch (err) {
+          assert.equal(err.error.errorCode.code, "NothingToWithdraw");
+        }
+
+        await new Promise((resolve) => setTimeout(resolve, 3000));
+
+        const balanceBefore = await provider.connection.getTokenAccountBalance(userTokenAccount);
+        await program.methods.withdrawUnbonded().accounts(unstakeAccounts).signers([authority]).rpc();
+        const balanceAfter = await provider.connection.getTokenAccountBalance(userTokenAccount);
+        assert.equal(
+          balanceAfter.value.amount,
+          (BigInt(balanceBefore.value.amount) + BigInt(stakeAmount.toString())).toString()
+        );
+      });
+
+      // What a stake could redeem right now: its shares at the current price
+      // plus the early-exit penalties it has been credited but not harvested
+      const redeemable = async (stake: PublicKey) => {
+        const vaultAccount = await program.account.vault.fetch(vault);
+        const stakeAccount = await program.account.userStake.fetch(stake);
+        return stakeAccount.shares
+          .mul(vaultAccount.totalStaked)
+          .div(vaultAccount.totalShares)
+          .add(
+            stakeAccount.weight
+              .mul(vaultAccount.accPenaltyPerShare.sub(stakeAccount.penaltyDebt))
+              .div(new anchor.BN(10).pow(new anchor.BN(12)))
+          );
+      };
+
+      it("Charges an early-exit penalty that stays with the remaining share holders", async () => {
+        // 10% penalty for leaving within an hour; the locked staker from the
+        // earlier test is still in the vault to receive it
+        await program.methods
+          .setExitPenalty(new anchor.BN(3600), 1_000)
+          .accounts({ vault, authority: authority.publicKey })
+          .signers([authority])
+          .rpc();
+        await program.methods
+          .setCooldown(new anchor.BN(0))
+          .accounts({ vault, authority: authority.publicKey })
+          .signers([authority])
+          .rpc();
+
+        const stakedBefore = (await program.account.vault.fetch(vault)).totalStaked;
+        await program.methods
+          .stake(stakeAmount, 0)
+          .accounts({
+            vault,
+            userStake,
+            userTokenAccount,
+            vaultTokenAccount: tokenAccount.publicKey,
+            mint,
+            shareMint,
+            shareEscrow,
+            vaultAuthority,
+            authority: authority.publicKey,
+            tokenProgram,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc();
+
+        // Leave in two halves: each pays 10% and the locker, the only other
+        // staker, can redeem that much more afterwards
+        const half = stakeAmount.divn(2);
+        const penalty = half.divn(10);
+        for (let i = 0; i < 2; i++) {
+          const lockerBefore = await redeemable(lockerStake);
+          const balanceBefore = await provider.connection.getTokenAccountBalance(userTokenAccount);
+          await program.methods
+            .unstake(half)
+            .accounts(unstakeAccounts())
+            .signers([authority])
+            .rpc();
+
+          const balanceAfter = await provider.connection.getTokenAccountBalance(userTokenAccount);
+          assert.equal(
+            balanceAfter.value.amount,
+            (BigInt(balanceBefore.value.amount) + BigInt(half.sub(penalty).toString())).toString()
+          );
+          const lockerAfter = await redeemable(lockerStake);
+          assert.approximately(lockerAfter.sub(lockerBefore).toNumber(), penalty.toNumber(), 1);
+        }
+
+        // The penalty is set aside for the remaining stakers instead of
+        // raising the share price
+        const vaultAccount = await program.account.vault.fetch(vault);
+        assert.equal(vaultAccount.totalStaked.toString(), stakedBefore.toString());
+        assert.ok(vaultAccount.accPenaltyPerShare.gtn(0));
+      });
+
+      it("Doesn't let a partial early exit earn back its own penalty", async () => {
+        await program.methods
+          .stake(stakeAmount, 0)
+          .accounts({
+            vault,
+            userStake,
+            userTokenAccount,
+            vaultTokenAccount: tokenAccount.publicKey,
+            mint,
+            shareMint,
+            shareEscrow,
+            vaultAuthority,
+            authority: authority.publicKey,
+            tokenProgram,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc();
+
+        // Park a quarter of the shares in a wallet, then leave with another quarter
+        const walletShareAccount = await createAccount(
+          provider.connection, payer, shareMint, authority.publicKey, undefined, undefined, tokenProgram
+        );
+        const quarter = stakeAmount.divn(4);
+        await program.methods
+          .withdrawShares(quarter)
+          .accounts({
+            vault,
+            userStake,
+            shareMint,
+            shareEscrow,
+            destination: walletShareAccount,
+            vaultAuthority,
+            authority: authority.publicKey,
+            tokenProgram,
+          })
+          .signers([authority])
+          .rpc();
+
+        const valueOf = (vaultAccount: any, shares: anchor.BN) =>
+          shares.mul(vaultAccount.totalStaked).div(vaultAccount.totalShares);
+        const vaultBefore = await program.account.vault.fetch(vault);
+        const keptBefore = valueOf(vaultBefore, stakeAmount.sub(quarter));
+
+        const balanceBefore = await provider.connection.getTokenAccountBalance(userTokenAccount);
+        await program.methods
+          .unstake(quarter)
+          .accounts(unstakeAccounts())
+          .signers([authority])
+          .rpc();
+        const balanceAfter = await provider.connection.getTokenAccountBalance(userTokenAccount);
+        const exited = valueOf(vaultBefore, quarter);
+        assert.equal(
+          balanceAfter.value.amount,
+          (BigInt(balanceBefore.value.amount) + BigInt(exited.sub(exited.divn(10)).toString())).toString()
+        );
+
+        // Neither the shares left in the stake nor those in the wallet gain
+        // from the penalty, and the stake has no claim on it
+        const vaultAfter = await program.account.vault.fetch(vault);
+        const stakeAccount = await program.account.userStake.fetch(userStake);
+        assert.equal(stakeAccount.shares.toString(), quarter.muln(2).toString());
+        assert.equal(
+          valueOf(vaultAfter, stakeAmount.sub(quarter)).toString(),
+          keptBefore.toString()
+        );
+        assert.equal(stakeAccount.penaltyDebt.toString(), vaultAfter.accPenaltyPerShare.toString());
+      });
+
+      it("Compounds rewards paid in the staking mint, directly and via a keeper", async () => {
+        // Stream 2 pays in the staking mint itself
+        const compoundVault = Keypair.generate();
+        await program.methods
+          .addRewardStream(rewardRate)
+          .accounts({
+            vault,
+            vaultAuthority,
+            rewardVault: compoundVault.publicKey,
+            rewardMint: mint,
+            authority: authority.publicKey,
+            rewardTokenProgram: tokenProgram,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority, compoundVault])
+          .rpc();
+        await program.methods
+          .fundRewards(2, new anchor.BN(100_000_000))
+          .accounts({
+            ...fundRewardsAccounts(),
+            rewardVault: compoundVault.publicKey,
+            adminRewardAta: userTokenAccount,
+            rewardMint: mint,
+          })
+          .signers([authority])
+          .rpc();
+        await program.methods
+          .stake(stakeAmount, 0)
+          .accounts({
+            vault,
+            userStake,
+            userTokenAccount,
+            vaultTokenAccount: tokenAccount.publicKey,
+            mint,
+            shareMint,
+            shareEscrow,
+            vaultAuthority,
+            authority: authority.publicKey,
+            tokenProgram,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc();
+
+        const compoundAccounts = {
+          vault,
+          userStake,
+          vaultAuthority,
+          rewardVault: compoundVault.publicKey,
+          vaultTokenAccount: tokenAccount.publicKey,
+          mint,
+          shareMint,
+          shareEscrow,
+          tokenProgram,
+        };
+
+        await new Promise((resolve) => setTimeout(resolve, 2000));
+        const sharesBefore = (await program.account.userStake.fetch(userStake)).shares;
+        await program.methods
+          .compound(2)
+          .accounts({ ...compoundAccounts, staker: authority.publicKey })
+          .signers([authority])
+          .rpc();
+        const sharesAfterCompound = (await program.account.userStake.fetch(userStake)).shares;
+        assert.ok(sharesAfterCompound.gt(sharesBefore));
+
+        // Keepers may only compound stakes that opted in
+        const keeper = Keypair.generate();
+        await new Promise((resolve) => setTimeout(resolve, 2000));
+        try {
+          await program.methods
+            .crankCompound(2)
+            .accounts({ ...compoundAccounts, keeper: keeper.publicKey })
+            .signers([keeper])
+            .rpc();
+          assert.fail("cranking without opt-in should fail");
+        } catch (err) {
+          assert.equal(err.error.errorCode.code, "AutoCompoundDisabled");
+        }
+
+        await program.methods
+          .setAutoCompound(true)
+          .accounts({ vault, userStake, staker: authority.publicKey })
+          .signers([authority])
+          .rpc();
+        await program.methods
+          .crankCompound(2)
+          .accounts({ ...compoundAccounts, keeper: keeper.publicKey })
+          .signers([keeper])
+          .rpc();
+        const sharesAfterCrank = (await program.account.userStake.fetch(userStake)).shares;
+        assert.ok(sharesAfterCrank.gt(sharesAfterCompound));
+      });
+
+      it("Moves reward weight only with shares deposited into the escrow", async () => {
+        const receiver = Keypair.generate();
+        await provider.connection.confirmTransaction(
+          await provider.connection.requestAirdrop(receiver.publicKey, LAMPORTS_PER_SOL)
+        );
+        const [receiverStake] = PublicKey.findProgramAddressSync(
+          [Buffer.from("user-stake"), receiver.publicKey.toBuffer(), vault.toBuffer()],
+          program.programId
+        );
+        const senderShareAccount = await createAccount(
+          provider.connection, payer, shareMint, authority.publicKey, undefined, undefined, tokenProgram
+        );
+        const receiverShareAccount = await createAccount(
+          provider.connection, payer, shareMint, receiver.publicKey, undefined, undefined, tokenProgram
+        );
+
+        const before = await program.account.userStake.fetch(userStake);
+        const totalWeightBefore = (await program.account.vault.fetch(vault)).totalWeight;
+        const sent = before.shares.divn(2);
+
+        await program.methods
+          .withdrawShares(sent)
+          .accounts({
+            vault,
+            userStake,
+            shareMint,
+            shareEscrow,
+            destination: senderShareAccount,
+            vaultAuthority,
+            authority: authority.publicKey,
+            tokenProgram,
+          })
+          .signers([authority])
+          .rpc();
+
+        // Shares sitting in a wallet don't earn for anyone
+        const sender = await program.account.userStake.fetch(userStake);
+        assert.equal(sender.shares.toString(), before.shares.sub(sent).toString());
+        const vaultWithdrawn = await program.account.vault.fetch(vault);
+        assert.equal(vaultWithdrawn.totalWeight.toString(), totalWeightBefore.sub(sent).toString());
+
+        await transfer(
+          provider.connection, payer, senderShareAccount, receiverShareAccount, authority,
+          BigInt(sent.toString()), [], undefined, tokenProgram
+        );
+        await program.methods
+          .depositShares(sent)
+          .accounts({
+            vault,
+            userStake: receiverStake,
+            shareMint,
+            userShareAccount: receiverShareAccount,
+            shareEscrow,
+            vaultAuthority,
+            authority: receiver.publicKey,
+            tokenProgram,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([receiver])
+          .rpc();
+
+        // The moved shares count once, now for the receiver
+        const receiverAccount = await program.account.userStake.fetch(receiverStake);
+        assert.ok(receiverAccount.staker.equals(receiver.publicKey));
+        assert.equal(receiverAccount.shares.toString(), sent.toString());
+        assert.equal(receiverAccount.weight.toString(), sent.toString());
+        const vaultAfter = await program.account.vault.fetch(vault);
+        assert.equal(vaultAfter.totalWeight.toString(), totalWeightBefore.toString());
+
+        // A deposit restarts the early-exit clock, so the freshly moved
+        // shares can't be cashed out without the penalty
+        const receiverTokenAccount = await createAccount(
+          provider.connection, payer, mint, receiver.publicKey, undefined, undefined, tokenProgram
+        );
+        const value = BigInt(sent.toString()) * BigInt(vaultAfter.totalStaked.toString())
+          / BigInt(vaultAfter.totalShares.toString());
+        await program.methods
+          .unstake(sent)
+          .accounts({
+            ...unstakeAccounts(),
+            userStake: receiverStake,
+            userTokenAccount: receiverTokenAccount,
+            authority: receiver.publicKey,
+          })
+          .signers([receiver])
+          .rpc();
+        const received = await provider.connection.getTokenAccountBalance(receiverTokenAccount);
+        assert.equal(received.value.amount, (value - value / BigInt(10)).toString());
+      });
+
+      it("Doesn't pay out the unfunded gap when the rate changes after the period ended", async () => {
+        // Stream 3 is funded for two seconds at the base rate
+        const shortVault = Keypair.generate();
+        await program.methods
+          .addRewardStream(rewardRate)
+          .accounts({
+            vault,
+            vaultAuthority,
+            rewardVault: shortVault.publicKey,
+            rewardMint,
+            authority: authority.publicKey,
+            rewardTokenProgram: tokenProgram,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority, shortVault])
+          .rpc();
+        await program.methods
+          .fundRewards(3, rewardRate.muln(2))
+          .accounts({ ...fundRewardsAccounts(), rewardVault: shortVault.publicKey })
+          .signers([authority])
+          .rpc();
+        const funded = (await program.account.vault.fetch(vault)).rewardStreams[3];
+
+        await new Promise((resolve) => setTimeout(resolve, 4000));
+        const setRate = () =>
+          program.methods
+            .setRewardRate(3, rewardRate.muln(2), new anchor.BN(0))
+            .accounts({ vault, authority: authority.publicKey })
+            .signers([authority])
+            .rpc();
+        await setRate();
+
+        // The lapsed stream restarts from the change, with nothing left to emit
+        const lapsed = (await program.account.vault.fetch(vault)).rewardStreams[3];
+        assert.isAbove(lapsed.lastRewardTs.toNumber(), funded.rewardEndTs.toNumber());
+        assert.equal(lapsed.rewardEndTs.toString(), lapsed.lastRewardTs.toString());
+
+        await new Promise((resolve) => setTimeout(resolve, 1500));
+        await setRate();
+        const after = (await program.account.vault.fetch(vault)).rewardStreams[3];
+        assert.equal(after.accRewardPerShare.toString(), lapsed.accRewardPerShare.toString());
+      });
+    });
+  }
+
+  it("Credits only what arrives from a Token-2022 transfer-fee mint", async () => {
+    const feeBasisPoints = 100; // 1%
+    const maxFee = BigInt(1_000_000_000);
+    const tokenProgram = TOKEN_2022_PROGRAM_ID;
+    const authority = Keypair.generate();
+    const tokenAccount = Keypair.generate();
+    const rewardVault = Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(authority.publicKey, 2 * LAMPORTS_PER_SOL)
+    );
+
+    // Stakes and rewards both use the fee mint, so stream 0 can be compounded
+    const feeMint = Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    const lamports = await provider.connection.getMinimumBalanceForRentExemption(mintLen);
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: provider.publicKey,
+          newAccountPubkey: feeMint.publicKey,
+          space: mintLen,
+          lamports,
+          programId: tokenProgram,
+        }),
+        createInitializeTransferFeeConfigInstruction(
+          feeMint.publicKey,
+          provider.publicKey,
+          provider.publicKey,
+          feeBasisPoints,
+          maxFee,
+          tokenProgram
+        ),
+        createInitializeMintInstruction(feeMint.publicKey, 6, provider.publicKey, null, tokenProgram)
+      ),
+      [feeMint]
+    );
+    const mint = feeMint.publicKey;
+    const userTokenAccount = await createAccount(
+      provider.connection, payer, mint, authority.publicKey, undefined, undefined, tokenProgram
+    );
+    await mintTo(
+      provider.connection, payer, mint, userTokenAccount, provider.publicKey,
+      1_000_000_000, [], undefined, tokenProgram
+    );
+    // What reaches the other side of a transfer of `amount`
+    const afterFee = (amount: bigint) =>
+      amount - (amount * BigInt(feeBasisPoints) + BigInt(9_999)) / BigInt(10_000);
+
+    const [vault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), authority.publicKey.toBuffer()],
+      program.programId
+    );
+    const [vaultAuthority, authorityBump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("authority"), vault.toBuffer()],
+      program.programId
+    );
+    const [userStake] = PublicKey.findProgramAddressSync(
+      [Buffer.from("user-stake"), authority.publicKey.toBuffer(), vault.toBuffer()],
+      program.programId
+    );
+    const [shareMint] = PublicKey.findProgramAddressSync(
+      [Buffer.from("shares"), vault.toBuffer()],
+      program.programId
+    );
+    const [shareEscrow] = PublicKey.findProgramAddressSync(
+      [Buffer.from("share-escrow"), vault.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .initializeVault(0, authorityBump, rewardRate)
+      .accounts({
+        vault,
+        vaultAuthority,
+        tokenAccount: tokenAccount.publicKey,
+        mint,
+        rewardVault: rewardVault.publicKey,
+        rewardMint: mint,
+        shareMint,
+        shareEscrow,
+        payer: authority.publicKey,
+        tokenProgram,
+        rewardTokenProgram: tokenProgram,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority, tokenAccount, rewardVault])
+      .rpc();
+
+    // Stake: shares and total_staked follow what reached the vault
+    await program.methods
+      .stake(stakeAmount, 0)
+      .accounts({
+        vault,
+        userStake,
+        userTokenAccount,
+        vaultTokenAccount: tokenAccount.publicKey,
+        mint,
+        shareMint,
+        shareEscrow,
+        vaultAuthority,
+        authority: authority.publicKey,
+        tokenProgram,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const staked = afterFee(BigInt(stakeAmount.toString())).toString();
+    const stakeAccount = await program.account.userStake.fetch(userStake);
+    let vaultAccount = await program.account.vault.fetch(vault);
+    assert.equal(stakeAccount.shares.toString(), staked);
+    assert.equal(vaultAccount.totalStaked.toString(), staked);
+    assert.equal(
+      (await provider.connection.getTokenAccountBalance(tokenAccount.publicKey)).value.amount,
+      staked
+    );
+
+    // Funding: only the received amount is budgeted for emission
+    const funding = new anchor.BN(100_000_000);
+    await program.methods
+      .fundRewards(0, funding)
+      .accounts({
+        vault,
+        vaultAuthority,
+        rewardVault: rewardVault.publicKey,
+        adminRewardAta: userTokenAccount,
+        rewardMint: mint,
+        authority: authority.publicKey,
+        tokenProgram,
+      })
+      .signers([authority])
+      .rpc();
+
+    vaultAccount = await program.account.vault.fetch(vault);
+    assert.equal(
+      vaultAccount.rewardStreams[0].totalRewardsFunded.toString(),
+      afterFee(BigInt(funding.toString())).toString()
+    );
+
+    // Compounding: the payout is charged to the stream, but only what reaches
+    // the stake pool is added to the stake
+    await new Promise((resolve) => setTimeout(resolve, 2000));
+    const before = await program.account.vault.fetch(vault);
+    await program.methods
+      .compound(0)
+      .accounts({
+        vault,
+        userStake,
+        vaultAuthority,
+        rewardVault: rewardVault.publicKey,
+        vaultTokenAccount: tokenAccount.publicKey,
+        mint,
+        shareMint,
+        shareEscrow,
+        tokenProgram,
+        staker: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc();
+
+    const after = await program.account.vault.fetch(vault);
+    const payout = BigInt(
+      after.rewardStreams[0].totalRewardsDistributed
+        .sub(before.rewardStreams[0].totalRewardsDistributed)
+        .toString()
+    );
+    assert.ok(payout > BigInt(0));
+    assert.equal(
+      after.totalStaked.sub(before.totalStaked).toString(),
+      afterFee(payout).toString()
+    );
   });
 });